@@ -19,10 +19,10 @@ fn main() {
 /// implementation of json_schema_file macro code
 pub fn json_schema_file_impl(file_path: String, custom_name_map: &HashMap<String, String>, custom_type_map: &HashMap<String, String>) -> String {
     let schema_text: String = fs::read_to_string(&file_path)
-        .expect(format!("Could not read JSON Schema file: {}\n", &file_path).as_str());
+        .unwrap_or_else(|_| panic!("Could not read JSON Schema file: {}\n", &file_path));
     let struct_text = json_schema_to_struct(&schema_text, custom_name_map, custom_type_map);
     match struct_text {
-        Ok(rslt)      => return rslt,
+        Ok(rslt)      => rslt,
         Err(err_msg)   => panic!("Could not parse error {} from JSON Schema {}\n", err_msg, schema_text)
     }
 }
@@ -43,7 +43,7 @@ pub fn json_schema_to_struct(schema_text: &str, custom_name_map: &HashMap<String
 
 /// convert JSON Schema in a serde JSON map to a Rust struct
 fn json_schema_map_to_struct(schema_json_map_raw: &Map<String, Value>, custom_name_map: &HashMap<String, String>, custom_type_map: &HashMap<String, String>) -> Result<String> {
-    let title: String = if schema_json_map_raw.contains_key("title") == false || schema_json_map_raw["title"].as_str() == None {
+    let title: String = if !schema_json_map_raw.contains_key("title") || schema_json_map_raw["title"].as_str().is_none() {
         if custom_name_map.contains_key("") {
             custom_name_map.get("").unwrap().to_string()
         } else {
@@ -53,7 +53,7 @@ fn json_schema_map_to_struct(schema_json_map_raw: &Map<String, Value>, custom_na
         format_struct_name(schema_json_map_raw["title"].as_str().unwrap(), custom_name_map)
     };
     let schema_json_map = process_embedded_objects_into_defs(&title, schema_json_map_raw);
-    if schema_json_map.contains_key("properties") == false {
+    if !schema_json_map.contains_key("properties") {
         panic!("Could not parse JSON Schema, no properties\n");
     }
     let mut rslt: String = format!("#[derive(Clone, Serialize, Deserialize, Default)]\r\npub struct {} {{\n", title);
@@ -66,11 +66,11 @@ fn json_schema_map_to_struct(schema_json_map_raw: &Map<String, Value>, custom_na
             rslt = format!("{}{}", rslt, field_text);
         }
     }
-    rslt = rslt + "}\n";
+    rslt += "}\n";
     if schema_json_map.contains_key("$defs") {
         rslt = format!("{}{}", rslt, process_defs(&schema_json_map["$defs"], custom_name_map, custom_type_map));
     }
-    return Ok(rslt);
+    Ok(rslt)
 }
 
 /// move embedded objects into the $defs
@@ -91,12 +91,12 @@ fn process_embedded_objects_into_defs(struct_name: &str, schema_json_map: &Map<S
         }
         revised_schema_json_map.insert("$defs".to_string(), Value::Object(defs_map));  
     }
-    return revised_schema_json_map;
+    revised_schema_json_map
 } 
 
 /// extract embedded objects 
 fn extract_embedded_objects(name_to_field: &str, schema_json_map_section: &Map<String, Value>, new_defs: & mut HashMap<String, Map<String, Value>>, is_root: bool) -> Map<String, Value> {
-    if schema_json_map_section.contains_key("type") == false {
+    if !schema_json_map_section.contains_key("type") {
         // may be a $ref
         return schema_json_map_section.clone();
     }
@@ -111,7 +111,7 @@ fn extract_embedded_objects(name_to_field: &str, schema_json_map_section: &Map<S
         let array_name = format!("{}_item", name_to_field);
         if let Value::Object(items_type) = &schema_json_map_section["items"] {
             let mut new_schema_json_map_section = schema_json_map_section.clone();
-            new_schema_json_map_section["items"] = Value::Object(extract_embedded_objects(&array_name, &items_type, new_defs, false));
+            new_schema_json_map_section["items"] = Value::Object(extract_embedded_objects(&array_name, items_type, new_defs, false));
             return new_schema_json_map_section;
         } else {
             panic!("Can't find item type for {}", array_name);
@@ -136,7 +136,7 @@ fn extract_embedded_objects(name_to_field: &str, schema_json_map_section: &Map<S
         new_schema_json_map_section = Map::new();
         new_schema_json_map_section.insert("$ref".to_string(), Value::String(format!("#/$defs/{}", name_to_field)));
     }
-    return new_schema_json_map_section;
+    new_schema_json_map_section
 }
 
 /// process the $defs field
@@ -180,12 +180,12 @@ fn get_field_text(key_name: &str, defn_value: &Value, custom_name_map: &HashMap<
     } else {
         panic!("Could not parse JSON Schema, bad defintion for {}\n", key_name);
     }   
-    return format!("    #[serde(default)]\n    pub {}: {},\n", field_name, rust_type_name);
+    format!("    #[serde(default)]\n    pub {}: {},\n", field_name, rust_type_name)
 } 
 
 /// get the rust field type from definition JSON object
 fn get_field_type(key_name: &str, defn_map: Map<String, Value>, custom_name_map: &HashMap<String, String>) -> String {
-    if defn_map.contains_key("type") == false {
+    if !defn_map.contains_key("type") {
         // $ref
         if let Value::String(ref_name) = &defn_map["$ref"] {
             if &ref_name[0..8] == "#/$defs/" {
@@ -207,12 +207,12 @@ fn get_field_type(key_name: &str, defn_map: Map<String, Value>, custom_name_map:
                             } else {
                                 panic!("Could not parse JSON Schema, no array item type for {}\n", key_name);
                             };
-                            return format!("Vec<{}>", item_type_name);
+                            format!("Vec<{}>", item_type_name)
                         },
         _            => {
-                            return get_simple_rust_type(json_type_name);
+                            get_simple_rust_type(json_type_name)
                         }     
-    };
+    }
 }
 
 
@@ -226,7 +226,7 @@ fn get_simple_rust_type(json_type_name: &str) -> String {
         "object"     => "serde_json::Value::Object",
         _            => panic!("Could not parse JSON Schema, unknown type {}\n", json_type_name)
     };
-    return rust_type_name.to_string();
+    rust_type_name.to_string()
 }
 
 /// apply name changes, or else convert string to Capital Case 
@@ -235,5 +235,5 @@ fn format_struct_name(src: &str, custom_name_map: &HashMap<String, String>) -> S
         return custom_name_map.get(src).unwrap().to_string();
     } 
     let lc: String = src.to_string();
-    return lc[0..1].to_uppercase() + &lc[1..];
+    lc[0..1].to_uppercase() + &lc[1..]
 }
\ No newline at end of file
@@ -38,10 +38,10 @@ pub fn json_schema_file_impl(
     custom_type_map: &HashMap<String, String>,
 ) -> String {
     let schema_text: String = fs::read_to_string(&file_path)
-        .expect(format!("Could not read JSON Schema file: {}\n", &file_path).as_str());
+        .unwrap_or_else(|_| panic!("Could not read JSON Schema file: {}\n", &file_path));
     let struct_text = json_schema_to_struct(&schema_text, custom_name_map, custom_type_map);
     match struct_text {
-        Ok(rslt) => return rslt,
+        Ok(rslt) => rslt,
         Err(err_msg) => panic!(
             "Could not parse error {} from JSON Schema {}\n",
             err_msg, schema_text
@@ -73,8 +73,8 @@ fn json_schema_map_to_struct(
     custom_name_map: &HashMap<String, String>,
     custom_type_map: &HashMap<String, String>,
 ) -> Result<String> {
-    let title: String = if schema_json_map_raw.contains_key("title") == false
-        || schema_json_map_raw["title"].as_str() == None
+    let title: String = if !schema_json_map_raw.contains_key("title")
+        || schema_json_map_raw["title"].as_str().is_none()
     {
         if custom_name_map.contains_key("") {
             custom_name_map.get("").unwrap().to_string()
@@ -88,7 +88,7 @@ fn json_schema_map_to_struct(
         )
     };
     let schema_json_map = process_embedded_objects_into_defs(&title, schema_json_map_raw);
-    if schema_json_map.contains_key("properties") == false {
+    if !schema_json_map.contains_key("properties") {
         panic!("Could not parse JSON Schema, no properties\n");
     }
     let mut rslt: String = format!(
@@ -105,7 +105,7 @@ fn json_schema_map_to_struct(
             rslt = format!("{}{}", rslt, field_text);
         }
     }
-    rslt = rslt + "}\n";
+    rslt += "}\n";
     if schema_json_map.contains_key("$defs") {
         rslt = format!(
             "{}{}",
@@ -113,7 +113,7 @@ fn json_schema_map_to_struct(
             process_defs(&schema_json_map["$defs"], custom_name_map, custom_type_map)
         );
     }
-    return Ok(rslt);
+    Ok(rslt)
 }
 
 /// move embedded objects into the $defs
@@ -138,7 +138,7 @@ fn process_embedded_objects_into_defs(
         }
         revised_schema_json_map.insert("$defs".to_string(), Value::Object(defs_map));
     }
-    return revised_schema_json_map;
+    revised_schema_json_map
 }
 
 /// extract embedded objects
@@ -148,7 +148,7 @@ fn extract_embedded_objects(
     new_defs: &mut HashMap<String, Map<String, Value>>,
     is_root: bool,
 ) -> Map<String, Value> {
-    if schema_json_map_section.contains_key("type") == false {
+    if !schema_json_map_section.contains_key("type") {
         // may be a $ref
         return schema_json_map_section.clone();
     }
@@ -165,7 +165,7 @@ fn extract_embedded_objects(
             let mut new_schema_json_map_section = schema_json_map_section.clone();
             new_schema_json_map_section["items"] = Value::Object(extract_embedded_objects(
                 &array_name,
-                &items_type,
+                items_type,
                 new_defs,
                 false,
             ));
@@ -201,7 +201,7 @@ fn extract_embedded_objects(
             Value::String(format!("#/$defs/{}", name_to_field)),
         );
     }
-    return new_schema_json_map_section;
+    new_schema_json_map_section
 }
 
 /// process the $defs field
@@ -257,10 +257,10 @@ fn get_field_text(
             key_name
         );
     }
-    return format!(
+    format!(
         "    #[serde(default)]\n    pub {}: {},\n",
         field_name, rust_type_name
-    );
+    )
 }
 
 /// get the rust field type from definition JSON object
@@ -269,7 +269,7 @@ fn get_field_type(
     defn_map: Map<String, Value>,
     custom_name_map: &HashMap<String, String>,
 ) -> String {
-    if defn_map.contains_key("type") == false {
+    if !defn_map.contains_key("type") {
         // $ref
         if let Value::String(ref_name) = &defn_map["$ref"] {
             if &ref_name[0..8] == "#/$defs/" {
@@ -297,12 +297,12 @@ fn get_field_type(
                     key_name
                 );
             };
-            return format!("Vec<{}>", item_type_name);
+            format!("Vec<{}>", item_type_name)
         }
         _ => {
-            return get_simple_rust_type(json_type_name);
+            get_simple_rust_type(json_type_name)
         }
-    };
+    }
 }
 
 /// convert JSON Schema types to Rust equivalents
@@ -318,7 +318,7 @@ fn get_simple_rust_type(json_type_name: &str) -> String {
             json_type_name
         ),
     };
-    return rust_type_name.to_string();
+    rust_type_name.to_string()
 }
 
 /// apply name changes, or else convert string to Capital Case
@@ -327,7 +327,7 @@ fn format_struct_name(src: &str, custom_name_map: &HashMap<String, String>) -> S
         return custom_name_map.get(src).unwrap().to_string();
     }
     let lc: String = src.to_string();
-    return lc[0..1].to_uppercase() + &lc[1..];
+    lc[0..1].to_uppercase() + &lc[1..]
 }
 
 //cargo run --example process_example_file_nbformat
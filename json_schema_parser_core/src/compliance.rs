@@ -0,0 +1,353 @@
+//! Copyright (c) 2023  Ward van der Veer
+//!
+//! JSON-Schema-Test-Suite compliance harness
+//!
+//! `validator_passes_suite` runs the validator over the vendored suite on every `cargo test`.
+//!
+//! `support_matrix` additionally generates a Rust type for every suite schema, checks that each valid
+//! instance deserializes into it and round-trips, and rewrites COMPLIANCE.md with the results:
+//!
+//! cargo test -- --ignored support_matrix
+
+extern crate serde_json;
+use processor::{json_schema_to_struct, GeneratorOptions};
+use regex::Regex;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use draft::Draft;
+use validator::{json_equal, validate_with_remotes};
+
+/// location of the vendored JSON-Schema-Test-Suite
+const SUITE_DIR: &str = "tests/JSON-Schema-Test-Suite";
+
+/// meta-schemas the suite refers to by their canonical URI
+const META_SCHEMAS: &[(&str, &str)] = &[
+    ("http://json-schema.org/draft-04/schema", "tests/meta_schemas/draft-04.json"),
+    ("http://json-schema.org/draft-07/schema", "tests/meta_schemas/draft-07.json"),
+    ("https://json-schema.org/draft/2020-12/schema", "tests/meta_schemas/draft2020-12/schema.json"),
+    ("https://json-schema.org/draft/2020-12/meta/applicator", "tests/meta_schemas/draft2020-12/meta/applicator.json"),
+    ("https://json-schema.org/draft/2020-12/meta/content", "tests/meta_schemas/draft2020-12/meta/content.json"),
+    ("https://json-schema.org/draft/2020-12/meta/core", "tests/meta_schemas/draft2020-12/meta/core.json"),
+    ("https://json-schema.org/draft/2020-12/meta/format-annotation", "tests/meta_schemas/draft2020-12/meta/format-annotation.json"),
+    ("https://json-schema.org/draft/2020-12/meta/meta-data", "tests/meta_schemas/draft2020-12/meta/meta-data.json"),
+    ("https://json-schema.org/draft/2020-12/meta/unevaluated", "tests/meta_schemas/draft2020-12/meta/unevaluated.json"),
+    ("https://json-schema.org/draft/2020-12/meta/validation", "tests/meta_schemas/draft2020-12/meta/validation.json"),
+];
+
+/// the suite directories of the drafts the generator supports, which the matrix should cover
+const SUPPORTED_DRAFTS: &[&str] = &["draft4", "draft7", "draft2020-12"];
+
+/// the published support matrix
+const MATRIX_FILE: &str = "../COMPLIANCE.md";
+
+/// suite files ("draft/keyword") in which the validator is known to fail some cases
+const KNOWN_VALIDATOR_FAILURES: &[&str] = &[
+    "draft4/optional/format",
+];
+
+/// one file of the suite, normally covering a single keyword
+struct SuiteFile {
+    draft: String,
+    draft_override: Option<Draft>,
+    keyword: String,
+    groups: Vec<SuiteGroup>,
+}
+
+/// a schema with the instances it is tested against
+struct SuiteGroup {
+    description: String,
+    schema: Value,
+    tests: Vec<SuiteTest>,
+}
+
+/// a single instance and whether it is valid under the group's schema
+struct SuiteTest {
+    description: String,
+    data: Value,
+    valid: bool,
+}
+
+/// pass counts for one row of the support matrix
+#[derive(Default)]
+struct Tally {
+    validator_passed: usize,
+    validator_total: usize,
+    deserialize_passed: usize,
+    round_trip_passed: usize,
+    valid_total: usize,
+}
+
+/// read every draft directory of the suite
+fn load_suite() -> Vec<SuiteFile> {
+    let tests_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join(SUITE_DIR).join("tests");
+    let mut draft_dirs: Vec<PathBuf> = fs::read_dir(&tests_dir)
+        .unwrap_or_else(|_| panic!("Could not read test suite directory: {}\n", tests_dir.display()))
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.is_dir())
+        .collect();
+    draft_dirs.sort();
+    let mut suite: Vec<SuiteFile> = Vec::new();
+    for draft_dir in draft_dirs {
+        let draft = draft_dir.file_name().unwrap().to_string_lossy().to_string();
+        let draft_override = Draft::from_name(&draft);
+        for file_path in list_json_files(&draft_dir) {
+            let keyword = file_path.strip_prefix(&draft_dir).unwrap().with_extension("").to_string_lossy().replace('\\', "/");
+            suite.push(SuiteFile { draft: draft.clone(), draft_override, keyword, groups: load_suite_file(&file_path) });
+        }
+    }
+    suite
+}
+
+/// all .json files below a directory, sorted
+fn list_json_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = Vec::new();
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            files.extend(list_json_files(&path));
+        } else if path.extension().map(|ext| ext == "json").unwrap_or(false) {
+            files.push(path);
+        }
+    }
+    files.sort();
+    files
+}
+
+/// parse one suite file
+fn load_suite_file(file_path: &Path) -> Vec<SuiteGroup> {
+    let contents: String = fs::read_to_string(file_path)
+        .unwrap_or_else(|_| panic!("Could not read test suite file: {}\n", file_path.display()));
+    let groups: Value = serde_json::from_str(&contents)
+        .unwrap_or_else(|err_msg| panic!("Could not parse test suite file {}: {}\n", file_path.display(), err_msg));
+    groups.as_array().unwrap().iter().map(|group| SuiteGroup {
+        description: group["description"].as_str().unwrap_or("").to_string(),
+        schema: group["schema"].clone(),
+        tests: group["tests"].as_array().unwrap().iter().map(|test| SuiteTest {
+            description: test["description"].as_str().unwrap_or("").to_string(),
+            data: test["data"].clone(),
+            valid: test["valid"].as_bool().unwrap(),
+        }).collect(),
+    }).collect()
+}
+
+/// the suite's remote documents, keyed by the URI the tests refer to them by
+fn load_remotes() -> HashMap<String, Value> {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let remotes_dir = manifest_dir.join(SUITE_DIR).join("remotes");
+    let mut remotes: HashMap<String, Value> = HashMap::new();
+    for file_path in list_json_files(&remotes_dir) {
+        let relative = file_path.strip_prefix(&remotes_dir).unwrap().to_string_lossy().replace('\\', "/");
+        let contents: String = fs::read_to_string(&file_path).unwrap();
+        remotes.insert(format!("http://localhost:1234/{}", relative), serde_json::from_str(&contents).unwrap());
+    }
+    for (uri, file_path) in META_SCHEMAS {
+        let contents: String = fs::read_to_string(manifest_dir.join(file_path)).unwrap();
+        remotes.insert(uri.to_string(), serde_json::from_str(&contents).unwrap());
+    }
+    remotes
+}
+
+/// run the validator over one suite file, returning descriptions of the cases it gets wrong
+fn validator_failures(suite_file: &SuiteFile, remotes: &HashMap<String, Value>) -> Vec<String> {
+    let mut failures: Vec<String> = Vec::new();
+    for group in &suite_file.groups {
+        for test in &group.tests {
+            let is_valid = validate_with_remotes(&group.schema, &test.data, remotes, suite_file.draft_override).is_ok();
+            if is_valid != test.valid {
+                failures.push(format!("{}/{}: {} / {}", suite_file.draft, suite_file.keyword, group.description, test.description));
+            }
+        }
+    }
+    failures
+}
+
+#[test]
+fn validator_passes_suite() {
+    let remotes = load_remotes();
+    let mut unexpected: Vec<String> = Vec::new();
+    let mut stale: Vec<String> = Vec::new();
+    for suite_file in load_suite() {
+        let file_key = format!("{}/{}", suite_file.draft, suite_file.keyword);
+        let failures = validator_failures(&suite_file, &remotes);
+        let known = KNOWN_VALIDATOR_FAILURES.contains(&file_key.as_str());
+        if known && failures.is_empty() {
+            stale.push(file_key);
+        } else if !known {
+            unexpected.extend(failures);
+        }
+    }
+    assert!(unexpected.is_empty(), "validator fails suite cases:\n{}", unexpected.join("\n"));
+    assert!(stale.is_empty(), "suite files now pass, remove from KNOWN_VALIDATOR_FAILURES: {:?}", stale);
+}
+
+/// a suite schema that the generator produced a type for
+struct CodegenCase {
+    module: String,
+    code: String,
+    instances: Vec<Value>,
+}
+
+/// generate a type named Root for every suite schema the generator accepts
+fn generate_cases(suite: &[SuiteFile]) -> BTreeMap<(String, String), Vec<CodegenCase>> {
+    let mut options: GeneratorOptions = GeneratorOptions::default();
+    options.custom_name_map.insert("".to_string(), "Root".to_string());
+    let mut cases: BTreeMap<(String, String), Vec<CodegenCase>> = BTreeMap::new();
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let mut case_number = 0;
+    for suite_file in suite {
+        let file_cases = cases.entry((suite_file.draft.clone(), suite_file.keyword.clone())).or_default();
+        for group in &suite_file.groups {
+            let mut schema = group.schema.clone();
+            if let Value::Object(schema_map) = &mut schema {
+                schema_map.remove("title");
+            }
+            let schema_text = schema.to_string();
+            options.draft_override = suite_file.draft_override;
+            let generated = panic::catch_unwind(|| json_schema_to_struct(&schema_text, &options));
+            if let Ok(Ok(code)) = generated {
+                case_number += 1;
+                file_cases.push(CodegenCase {
+                    module: format!("case_{}", case_number),
+                    code,
+                    instances: group.tests.iter().filter(|test| test.valid).map(|test| test.data.clone()).collect(),
+                });
+            }
+        }
+    }
+    panic::set_hook(default_hook);
+    cases
+}
+
+/// compile the generated types into a scratch crate and round-trip every valid instance through them
+fn run_codegen_cases(cases: &[&CodegenCase]) -> HashMap<(String, usize), Option<Value>> {
+    let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("..").join("target").join("compliance");
+    let src_dir = crate_dir.join("src");
+    let _ = fs::remove_dir_all(&src_dir);
+    fs::create_dir_all(&src_dir).unwrap();
+    fs::write(crate_dir.join("Cargo.toml"), concat!(
+        "[package]\nname = \"compliance_cases\"\nversion = \"0.0.0\"\nedition = \"2021\"\n\n",
+        "[dependencies]\nserde = { version = \"1.0.197\", features = [\"derive\"] }\nserde_json = \"1.0.115\"\n\n",
+        "[workspace]\n",
+    )).unwrap();
+    let error_location = Regex::new(r"src/(case_\d+)\.rs").unwrap();
+    let mut remaining: Vec<&CodegenCase> = cases.to_vec();
+    loop {
+        let mut main_text = String::from("#![allow(non_camel_case_types, non_snake_case, dead_code, unused_imports)]\n\n");
+        let mut check_calls = String::new();
+        for case in &remaining {
+            fs::write(src_dir.join(format!("{}.rs", case.module)), &case.code).unwrap();
+            main_text += &format!("mod {};\n", case.module);
+            let instances_text = serde_json::to_string(&case.instances).unwrap();
+            check_calls += &format!("    check::<{}::Root>(\"{}\", {:?});\n", case.module, case.module, instances_text);
+        }
+        main_text += concat!(
+            "\nfn check<T: serde::de::DeserializeOwned + serde::Serialize>(module: &str, instances: &str) {\n",
+            "    let instances: Vec<serde_json::Value> = serde_json::from_str(instances).unwrap();\n",
+            "    for (position, instance) in instances.into_iter().enumerate() {\n",
+            "        match serde_json::from_value::<T>(instance) {\n",
+            "            Ok(typed) => println!(\"{} {} {}\", module, position, serde_json::to_value(&typed).unwrap()),\n",
+            "            Err(_)    => println!(\"{} {} -\", module, position),\n",
+            "        }\n",
+            "    }\n",
+            "}\n\nfn main() {\n",
+        );
+        main_text += &check_calls;
+        main_text += "}\n";
+        fs::write(src_dir.join("main.rs"), main_text).unwrap();
+        let output = Command::new(std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string()))
+            .args(["run", "--quiet", "--message-format=short"])
+            .current_dir(&crate_dir)
+            .output()
+            .expect("Could not run cargo for the compliance cases\n");
+        if output.status.success() {
+            let mut results: HashMap<(String, usize), Option<Value>> = HashMap::new();
+            for line in String::from_utf8_lossy(&output.stdout).lines() {
+                let mut parts = line.splitn(3, ' ');
+                let module = parts.next().unwrap().to_string();
+                let position: usize = parts.next().unwrap().parse().unwrap();
+                let round_trip = match parts.next().unwrap() {
+                    "-"  => None,
+                    text => Some(serde_json::from_str(text).unwrap()),
+                };
+                results.insert((module, position), round_trip);
+            }
+            return results;
+        }
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        let broken: Vec<String> = error_location.captures_iter(&stderr).map(|c| c[1].to_string()).collect();
+        if broken.is_empty() {
+            panic!("Could not build the compliance cases:\n{}", stderr);
+        }
+        remaining.retain(|case| !broken.contains(&case.module));
+        for module in broken {
+            let _ = fs::remove_file(src_dir.join(format!("{}.rs", module)));
+        }
+    }
+}
+
+/// render the support matrix as markdown
+fn render_matrix(tallies: &BTreeMap<(String, String), Tally>) -> String {
+    let mut rslt = String::from(concat!(
+        "# JSON Schema compliance\n\n",
+        "Generated by `cargo test -- --ignored support_matrix` from the vendored JSON-Schema-Test-Suite.\n\n",
+        "* validator - suite cases (valid and invalid instances) the validator classifies correctly\n",
+        "* deserialize - valid instances that deserialize into the generated type\n",
+        "* round-trip - valid instances that serialize back to the same JSON after deserializing\n\n",
+        "A schema the generator rejects counts as a failure for all of its valid instances.\n",
+    ));
+    let missing_drafts: Vec<&str> = SUPPORTED_DRAFTS.iter()
+        .filter(|supported_draft| !tallies.keys().any(|(draft, _)| draft == *supported_draft))
+        .copied()
+        .collect();
+    if !missing_drafts.is_empty() {
+        rslt += &format!("\nNot covered, as their suite is not vendored yet: {}.\n", missing_drafts.join(", "));
+    }
+    let mut current_draft = String::new();
+    for ((draft, keyword), tally) in tallies {
+        if *draft != current_draft {
+            rslt += &format!("\n## {}\n\n| keyword | validator | deserialize | round-trip |\n|---|---|---|---|\n", draft);
+            current_draft = draft.clone();
+        }
+        rslt += &format!("| {} | {}/{} | {}/{} | {}/{} |\n", keyword,
+            tally.validator_passed, tally.validator_total,
+            tally.deserialize_passed, tally.valid_total,
+            tally.round_trip_passed, tally.valid_total);
+    }
+    rslt
+}
+
+#[test]
+#[ignore]
+fn support_matrix() {
+    let suite = load_suite();
+    let remotes = load_remotes();
+    let cases = generate_cases(&suite);
+    let all_cases: Vec<&CodegenCase> = cases.values().flatten().collect();
+    let results = run_codegen_cases(&all_cases);
+    let mut tallies: BTreeMap<(String, String), Tally> = BTreeMap::new();
+    for suite_file in &suite {
+        let key = (suite_file.draft.clone(), suite_file.keyword.clone());
+        let tally = tallies.entry(key.clone()).or_default();
+        let total: usize = suite_file.groups.iter().map(|group| group.tests.len()).sum();
+        tally.validator_total = total;
+        tally.validator_passed = total - validator_failures(suite_file, &remotes).len();
+        tally.valid_total = suite_file.groups.iter().flat_map(|group| &group.tests).filter(|test| test.valid).count();
+        for case in &cases[&key] {
+            for (position, instance) in case.instances.iter().enumerate() {
+                if let Some(Some(round_trip)) = results.get(&(case.module.clone(), position)) {
+                    tally.deserialize_passed += 1;
+                    if json_equal(round_trip, instance) {
+                        tally.round_trip_passed += 1;
+                    }
+                }
+            }
+        }
+    }
+    let matrix_path = Path::new(env!("CARGO_MANIFEST_DIR")).join(MATRIX_FILE);
+    fs::write(&matrix_path, render_matrix(&tallies)).unwrap();
+}
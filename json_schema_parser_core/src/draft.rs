@@ -0,0 +1,118 @@
+//! Copyright (c) 2023  Ward van der Veer
+
+extern crate serde_json;
+use serde_json::Value;
+
+/// JSON Schema drafts with distinct keyword semantics, oldest first
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Draft {
+    Draft4,
+    Draft6,
+    Draft7,
+    Draft201909,
+    Draft202012,
+}
+
+impl Draft {
+    /// draft assumed when a schema has no (recognised) $schema
+    pub const DEFAULT: Draft = Draft::Draft202012;
+
+    /// recognise a $schema URI, e.g. "http://json-schema.org/draft-04/schema#"
+    pub fn from_schema_uri(uri: &str) -> Option<Draft> {
+        let trimmed = uri.trim_end_matches('#').trim_end_matches('/');
+        let trimmed = trimmed.strip_prefix("https://").or_else(|| trimmed.strip_prefix("http://")).unwrap_or(trimmed);
+        match trimmed {
+            "json-schema.org/draft-04/schema"      => Some(Draft::Draft4),
+            "json-schema.org/draft-06/schema"      => Some(Draft::Draft6),
+            "json-schema.org/draft-07/schema"      => Some(Draft::Draft7),
+            "json-schema.org/draft/2019-09/schema" => Some(Draft::Draft201909),
+            "json-schema.org/draft/2020-12/schema" => Some(Draft::Draft202012),
+            _                                      => None,
+        }
+    }
+
+    /// recognise a draft name as used for overrides, e.g. "draft-04", "draft7", "2020-12", or a $schema URI
+    pub fn from_name(name: &str) -> Option<Draft> {
+        let short_name = name.trim().to_lowercase().replace("draft", "").replace(['-', '_', ' '], "");
+        match short_name.as_str() {
+            "4" | "04"    => Some(Draft::Draft4),
+            "6" | "06"    => Some(Draft::Draft6),
+            "7" | "07"    => Some(Draft::Draft7),
+            "201909"      => Some(Draft::Draft201909),
+            "202012"      => Some(Draft::Draft202012),
+            _             => Draft::from_schema_uri(name.trim()),
+        }
+    }
+
+    /// the draft of a schema document: the override if given, else its $schema, else the default
+    pub fn detect(schema: &Value, draft_override: Option<Draft>) -> Draft {
+        if let Some(draft) = draft_override {
+            return draft;
+        }
+        match schema.get("$schema") {
+            Some(Value::String(uri)) => Draft::from_schema_uri(uri).unwrap_or(Draft::DEFAULT),
+            _                        => Draft::DEFAULT,
+        }
+    }
+
+    /// the $schema URI naming the draft
+    pub fn schema_uri(self) -> &'static str {
+        match self {
+            Draft::Draft4      => "http://json-schema.org/draft-04/schema#",
+            Draft::Draft6      => "http://json-schema.org/draft-06/schema#",
+            Draft::Draft7      => "http://json-schema.org/draft-07/schema#",
+            Draft::Draft201909 => "https://json-schema.org/draft/2019-09/schema",
+            Draft::Draft202012 => "https://json-schema.org/draft/2020-12/schema",
+        }
+    }
+
+    /// keyword holding reusable definitions, "definitions" before 2019-09, "$defs" after
+    pub fn defs_keyword(self) -> &'static str {
+        if self >= Draft::Draft201909 { "$defs" } else { "definitions" }
+    }
+
+    /// the defs keyword of the other drafts, which schemas still use alongside, or instead of, their own draft's
+    pub fn other_defs_keyword(self) -> &'static str {
+        if self >= Draft::Draft201909 { "definitions" } else { "$defs" }
+    }
+
+    /// keyword a schema declares its id with, "id" in draft 4, "$id" after
+    pub fn id_keyword(self) -> &'static str {
+        if self == Draft::Draft4 { "id" } else { "$id" }
+    }
+
+    /// before 2019-09 any keywords next to $ref are ignored
+    pub fn ref_overrides_siblings(self) -> bool {
+        self <= Draft::Draft7
+    }
+
+    /// draft 4 exclusiveMaximum/exclusiveMinimum are booleans modifying maximum/minimum
+    pub fn has_boolean_exclusive_bounds(self) -> bool {
+        self == Draft::Draft4
+    }
+
+    /// 2020-12 declares positional items with prefixItems, earlier drafts with an array-form items
+    pub fn has_prefix_items(self) -> bool {
+        self >= Draft::Draft202012
+    }
+
+    /// from draft 6 on, a number with a zero fractional part (e.g. 1.0) is an integer
+    pub fn integer_allows_zero_fraction(self) -> bool {
+        self >= Draft::Draft6
+    }
+
+    /// 2019-09 split dependencies into dependentRequired and dependentSchemas
+    pub fn has_split_dependencies(self) -> bool {
+        self >= Draft::Draft201909
+    }
+
+    /// if, then and else arrived in draft 7
+    pub fn has_conditionals(self) -> bool {
+        self >= Draft::Draft7
+    }
+
+    /// const, contains, propertyNames and boolean schemas arrived in draft 6
+    pub fn has_draft6_keywords(self) -> bool {
+        self >= Draft::Draft6
+    }
+}
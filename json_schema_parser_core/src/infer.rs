@@ -0,0 +1,235 @@
+//! Copyright (c) 2023  Ward van der Veer
+
+extern crate serde_json;
+use std::collections::BTreeMap;
+use regex::Regex;
+use serde_json::{json, Map, Number, Value};
+
+/// string formats recognised in samples, most specific first
+const FORMAT_PATTERNS: &[(&str, &str)] = &[
+    ("date-time", r"^\d{4}-\d{2}-\d{2}[Tt ]\d{2}:\d{2}:\d{2}(\.\d+)?([Zz]|[+-]\d{2}:\d{2})$"),
+    ("date",      r"^\d{4}-\d{2}-\d{2}$"),
+    ("time",      r"^\d{2}:\d{2}:\d{2}(\.\d+)?([Zz]|[+-]\d{2}:\d{2})?$"),
+    ("email",     r"^[^@\s]+@[^@\s]+\.[^@\s]+$"),
+    ("uuid",      r"^[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}$"),
+    ("uri",       r"^[a-zA-Z][a-zA-Z0-9+.-]*://\S+$"),
+    ("ipv4",      r"^(25[0-5]|2[0-4]\d|1?\d?\d)(\.(25[0-5]|2[0-4]\d|1?\d?\d)){3}$"),
+];
+
+/// settings for inferring a JSON Schema from samples
+#[derive(Clone, Debug)]
+pub struct InferOptions {
+    /// title of the root schema, which names the root struct
+    pub title: Option<String>,
+    /// most distinct values a string can have and still be an enum, 0 for no enums
+    pub max_enum_values: usize,
+    /// times each enum value must be seen on average, so that values seen once do not make an enum
+    pub min_enum_repeats: usize,
+    /// whether to look for string formats such as date-time and email
+    pub detect_formats: bool,
+}
+
+impl Default for InferOptions {
+    fn default() -> Self {
+        InferOptions { title: None, max_enum_values: 10, min_enum_repeats: 2, detect_formats: true }
+    }
+}
+
+/// what was seen at one place in the samples
+#[derive(Default)]
+struct Observed {
+    count: usize,
+    nulls: usize,
+    booleans: usize,
+    integers: usize,
+    big_integers: usize,
+    numbers: usize,
+    strings: Vec<String>,
+    objects: usize,
+    properties: BTreeMap<String, Observed>,
+    property_order: Vec<String>,
+    arrays: usize,
+    items: Option<Box<Observed>>,
+}
+
+impl Observed {
+    /// add one sample value
+    fn observe(&mut self, value: &Value) {
+        self.count += 1;
+        match value {
+            Value::Null       => self.nulls += 1,
+            Value::Bool(_)    => self.booleans += 1,
+            Value::Number(n)  => self.observe_number(n),
+            Value::String(s)  => self.strings.push(s.to_string()),
+            Value::Array(arr) => {
+                self.arrays += 1;
+                let items = self.items.get_or_insert_with(Box::default);
+                for item in arr {
+                    items.observe(item);
+                }
+            },
+            Value::Object(obj) => {
+                self.objects += 1;
+                for (prop_name, prop_value) in obj {
+                    if !self.properties.contains_key(prop_name) {
+                        self.property_order.push(prop_name.to_string());
+                    }
+                    self.properties.entry(prop_name.to_string()).or_default().observe(prop_value);
+                }
+            },
+        }
+    }
+
+    /// add one sample number, noting integers that do not fit an i32
+    fn observe_number(&mut self, n: &Number) {
+        match n.as_i64() {
+            Some(i) if i >= i32::MIN as i64 && i <= i32::MAX as i64 => self.integers += 1,
+            Some(_)                                                   => self.big_integers += 1,
+            None if n.as_u64().is_some()                              => self.big_integers += 1,
+            None                                                      => self.numbers += 1,
+        }
+    }
+
+    /// the schema for everything seen here
+    fn to_schema(&self, options: &InferOptions, format_regexes: &[(&str, Regex)]) -> Value {
+        let mut type_schemas: Vec<Value> = Vec::new();
+        if self.booleans > 0 {
+            type_schemas.push(json!({ "type": "boolean" }));
+        }
+        if self.numbers > 0 {
+            type_schemas.push(json!({ "type": "number" }));
+        } else if self.big_integers > 0 {
+            type_schemas.push(json!({ "type": "integer", "format": "int64" }));
+        } else if self.integers > 0 {
+            type_schemas.push(json!({ "type": "integer" }));
+        }
+        if !self.strings.is_empty() {
+            type_schemas.push(self.string_schema(options, format_regexes));
+        }
+        if self.arrays > 0 {
+            let items_schema: Value = match &self.items {
+                Some(items) if items.count > 0 => items.to_schema(options, format_regexes),
+                _                              => json!({}),
+            };
+            type_schemas.push(json!({ "type": "array", "items": items_schema }));
+        }
+        if self.objects > 0 {
+            let mut props_map: Map<String, Value> = Map::new();
+            let mut required: Vec<Value> = Vec::new();
+            for prop_name in &self.property_order {
+                let prop_observed: &Observed = &self.properties[prop_name];
+                props_map.insert(prop_name.to_string(), prop_observed.to_schema(options, format_regexes));
+                if prop_observed.count == self.objects {
+                    required.push(Value::String(prop_name.to_string()));
+                }
+            }
+            type_schemas.push(json!({ "type": "object", "properties": props_map, "required": required }));
+        }
+        let schema: Value = match type_schemas.len() {
+            0 if self.nulls > 0 => return json!({ "type": "null" }),
+            0                   => return json!({}),
+            1                   => type_schemas.remove(0),
+            _                   => json!({ "anyOf": type_schemas }),
+        };
+        if self.nulls > 0 {
+            return json!({ "anyOf": [schema, { "type": "null" }] });
+        }
+        schema
+    }
+
+    /// the schema for the strings seen here, with a format if they all share one, or else an enum if they repeat
+    fn string_schema(&self, options: &InferOptions, format_regexes: &[(&str, Regex)]) -> Value {
+        if options.detect_formats {
+            for (format_name, format_regex) in format_regexes {
+                if self.strings.iter().all(|s| format_regex.is_match(s)) {
+                    return json!({ "type": "string", "format": format_name });
+                }
+            }
+        }
+        let mut enum_values: Vec<&String> = Vec::new();
+        for s in &self.strings {
+            if !enum_values.contains(&s) {
+                enum_values.push(s);
+            }
+        }
+        if enum_values.len() <= options.max_enum_values && self.strings.len() >= enum_values.len() * options.min_enum_repeats.max(1) {
+            return json!({ "type": "string", "enum": enum_values });
+        }
+        json!({ "type": "string" })
+    }
+}
+
+/// infer a draft 2020-12 JSON Schema that every sample matches:
+/// properties missing from some samples are left out of required, repeated strings become enums,
+/// and strings that all share a format such as date-time, email or uuid are given it
+pub fn infer_schema(samples: &[Value], options: &InferOptions) -> Value {
+    let mut observed: Observed = Observed::default();
+    for sample in samples {
+        observed.observe(sample);
+    }
+    let format_regexes: Vec<(&str, Regex)> = FORMAT_PATTERNS.iter()
+        .map(|(format_name, pattern)| (*format_name, Regex::new(pattern).unwrap()))
+        .collect();
+    let mut schema_map: Map<String, Value> = Map::new();
+    schema_map.insert("$schema".to_string(), Value::String("https://json-schema.org/draft/2020-12/schema".to_string()));
+    if let Some(title) = &options.title {
+        schema_map.insert("title".to_string(), Value::String(title.to_string()));
+    }
+    if let Value::Object(inferred_map) = observed.to_schema(options, &format_regexes) {
+        schema_map.extend(inferred_map);
+    }
+    Value::Object(schema_map)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use processor::{json_schema_value_to_struct, GeneratorOptions};
+
+    #[test]
+    fn infer_merged_samples() {
+        let samples: Vec<Value> = vec![
+            json!({ "id": 1, "score": 2, "status": "active", "created": "2023-01-11T19:48:00Z", "email": "a@example.com", "note": null, "tags": ["x"] }),
+            json!({ "id": 2, "score": 2.5, "status": "active", "created": "2023-01-12T08:00:00.5+01:00", "email": "b@example.org", "note": "late" }),
+            json!({ "id": 3, "score": 3, "status": "closed", "created": "2023-01-13T09:30:00Z", "email": "c@example.net", "tags": [], "big": 5000000000_i64 }),
+            json!({ "id": 4, "score": 1, "status": "closed", "created": "2023-01-14T10:15:00Z", "email": "d@example.com" }),
+        ];
+        let options: InferOptions = InferOptions { title: Some("ticket".to_string()), ..InferOptions::default() };
+        let schema: Value = infer_schema(&samples, &options);
+        print!("{}\r\n", serde_json::to_string_pretty(&schema).unwrap());
+        assert_eq!(schema["$schema"], "https://json-schema.org/draft/2020-12/schema");
+        assert_eq!(schema["title"], "ticket");
+        assert_eq!(schema["properties"]["id"], json!({ "type": "integer" }));
+        assert_eq!(schema["properties"]["score"], json!({ "type": "number" }));
+        assert_eq!(schema["properties"]["big"], json!({ "type": "integer", "format": "int64" }));
+        assert_eq!(schema["properties"]["status"], json!({ "type": "string", "enum": ["active", "closed"] }));
+        assert_eq!(schema["properties"]["created"], json!({ "type": "string", "format": "date-time" }));
+        assert_eq!(schema["properties"]["email"], json!({ "type": "string", "format": "email" }));
+        assert_eq!(schema["properties"]["note"], json!({ "anyOf": [{ "type": "string" }, { "type": "null" }] }));
+        assert_eq!(schema["properties"]["tags"], json!({ "type": "array", "items": { "type": "string" } }));
+        assert_eq!(schema["required"], json!(["id", "score", "status", "created", "email"]));
+    }
+
+    #[test]
+    fn infer_randomuser_sample() {
+        let contents: String = fs::read_to_string("../examples/randomuser_sample_data.json").unwrap();
+        let sample: Value = serde_json::from_str(&contents).unwrap();
+        let options: InferOptions = InferOptions { title: Some("people".to_string()), ..InferOptions::default() };
+        let schema: Value = infer_schema(&[sample], &options);
+        let person: &Value = &schema["properties"]["results"]["items"];
+        assert_eq!(person["properties"]["email"], json!({ "type": "string", "format": "email" }));
+        assert_eq!(person["properties"]["login"]["properties"]["uuid"], json!({ "type": "string", "format": "uuid" }));
+        assert_eq!(person["properties"]["dob"]["properties"]["date"], json!({ "type": "string", "format": "date-time" }));
+        assert_eq!(person["properties"]["picture"]["properties"]["large"], json!({ "type": "string", "format": "uri" }));
+        assert_eq!(person["properties"]["location"]["properties"]["street"]["properties"]["number"], json!({ "type": "integer" }));
+        let ts = json_schema_value_to_struct(schema, &GeneratorOptions::default()).unwrap();
+        print!("{}\r\n", ts);
+        assert!(ts.contains("pub struct People {"));
+        assert!(ts.contains("pub results: Vec<People_results_item>,"));
+        assert!(ts.contains("pub email: String,"));
+        assert!(ts.contains("pub number: i32,"));
+    }
+
+}
@@ -0,0 +1,254 @@
+//! Copyright (c) 2023  Ward van der Veer
+
+extern crate serde_json;
+use std::path::Path;
+use serde_json::{json, Map, Result, Value};
+use processor::{json_schema_value_to_struct, GeneratorOptions};
+use source::{get_file_dependency_text, parse_document, read_schema_file, SourceFormat};
+
+/// keywords JSON Type Definition (RFC 8927) allows in a schema
+const JTD_KEYWORDS: &[&str] = &[
+    "definitions", "metadata", "nullable", "ref", "type", "enum", "elements",
+    "properties", "optionalProperties", "additionalProperties", "values", "discriminator", "mapping",
+];
+
+/// implementation of jtd_file macro code,
+/// the root type is named after the file unless renamed with "->name"
+pub fn jtd_file_impl(file_path: String, options: &GeneratorOptions) -> String {
+    let (resolved_path, jtd_text): (String, String) = read_schema_file(&file_path, "JSON Type Definition");
+    let mut file_options: GeneratorOptions = options.clone();
+    if !file_options.custom_name_map.contains_key("") {
+        let file_name: String = Path::new(&file_path).file_name().map(|file_name| file_name.to_string_lossy().to_string()).unwrap_or_default();
+        let root_name: &str = file_name.split('.').next().unwrap_or_default();
+        if root_name.is_empty() {
+            panic!("Could not parse JSON Type Definition, no root name for {}\n", file_path);
+        }
+        file_options.custom_name_map.insert("".to_string(), root_name[0..1].to_uppercase() + &root_name[1..]);
+    }
+    let source_format: SourceFormat = options.source_format.unwrap_or_else(|| SourceFormat::from_path(&file_path));
+    let jtd_value: Value = parse_document(&jtd_text, source_format, &file_path);
+    match json_schema_value_to_struct(jtd_to_json_schema(&jtd_value), &file_options) {
+        Ok(rslt)      => rslt + &get_file_dependency_text(&resolved_path),
+        Err(err_msg)   => panic!("Could not parse error {} from JSON Type Definition {}\n", err_msg, jtd_text)
+    }
+}
+
+/// convert a JSON Type Definition in a string slice to Rust types,
+/// the root type is named with the "" custom name
+pub fn jtd_to_struct(jtd_text: &str, options: &GeneratorOptions) -> Result<String> {
+    let jtd_value: Value = match serde_json::from_str(jtd_text) {
+        Ok(jv)        => jv,
+        Err(err_msg)  => panic!("Could not parse JSON error {}\n", err_msg)
+    };
+    json_schema_value_to_struct(jtd_to_json_schema(&jtd_value), options)
+}
+
+/// convert a JSON Type Definition to the equivalent draft 2020-12 JSON Schema
+pub fn jtd_to_json_schema(jtd_value: &Value) -> Value {
+    let jtd_map: &Map<String, Value> = match jtd_value {
+        Value::Object(jtd_map) => jtd_map,
+        _                      => panic!("Could not parse JSON Type Definition not JSON Object\n")
+    };
+    let mut schema_map: Map<String, Value> = jtd_form_to_schema("", jtd_map);
+    schema_map.insert("$schema".to_string(), Value::String("https://json-schema.org/draft/2020-12/schema".to_string()));
+    if let Some(definitions_value) = jtd_map.get("definitions") {
+        let definitions_map: &Map<String, Value> = match definitions_value {
+            Value::Object(definitions_map) => definitions_map,
+            _                              => panic!("Could not parse JSON Type Definition, invalid definitions\n")
+        };
+        let mut defs_map: Map<String, Value> = Map::new();
+        for (def_name, def_value) in definitions_map {
+            defs_map.insert(def_name.to_string(), Value::Object(jtd_form_to_schema(def_name, get_jtd_map(def_name, def_value))));
+        }
+        schema_map.insert("$defs".to_string(), Value::Object(defs_map));
+    }
+    Value::Object(schema_map)
+}
+
+/// convert one JSON Type Definition form, and whatever it contains, to JSON Schema
+fn jtd_form_to_schema(key_name: &str, jtd_map: &Map<String, Value>) -> Map<String, Value> {
+    for jtd_keyword in jtd_map.keys() {
+        if !JTD_KEYWORDS.contains(&jtd_keyword.as_str()) {
+            panic!("Could not parse JSON Type Definition, unknown keyword {} for {}\n", jtd_keyword, key_name);
+        }
+    }
+    let schema_value: Value = if let Some(ref_value) = jtd_map.get("ref") {
+        match ref_value {
+            Value::String(ref_name) => json!({ "$ref": format!("#/$defs/{}", ref_name) }),
+            _                       => panic!("Could not parse JSON Type Definition, invalid ref for {}\n", key_name)
+        }
+    } else if let Some(type_value) = jtd_map.get("type") {
+        match type_value.as_str() {
+            Some("boolean")   => json!({ "type": "boolean" }),
+            Some("string")    => json!({ "type": "string" }),
+            Some("timestamp") => json!({ "type": "string", "format": "date-time" }),
+            Some("float32")   => json!({ "type": "number", "format": "float" }),
+            Some("float64")   => json!({ "type": "number", "format": "double" }),
+            Some("int8")      => json!({ "type": "integer", "format": "int8", "minimum": i8::MIN, "maximum": i8::MAX }),
+            Some("uint8")     => json!({ "type": "integer", "format": "uint8", "minimum": u8::MIN, "maximum": u8::MAX }),
+            Some("int16")     => json!({ "type": "integer", "format": "int16", "minimum": i16::MIN, "maximum": i16::MAX }),
+            Some("uint16")    => json!({ "type": "integer", "format": "uint16", "minimum": u16::MIN, "maximum": u16::MAX }),
+            Some("int32")     => json!({ "type": "integer", "format": "int32", "minimum": i32::MIN, "maximum": i32::MAX }),
+            Some("uint32")    => json!({ "type": "integer", "format": "uint32", "minimum": u32::MIN, "maximum": u32::MAX }),
+            _                 => panic!("Could not parse JSON Type Definition, unknown type {} for {}\n", type_value, key_name)
+        }
+    } else if let Some(enum_value) = jtd_map.get("enum") {
+        match enum_value {
+            Value::Array(enum_values) if !enum_values.is_empty() && enum_values.iter().all(|enum_value| enum_value.is_string()) => json!({ "type": "string", "enum": enum_values }),
+            _ => panic!("Could not parse JSON Type Definition, invalid enum for {}\n", key_name)
+        }
+    } else if let Some(elements_value) = jtd_map.get("elements") {
+        let items_key_name: String = format!("{}[]", key_name);
+        json!({ "type": "array", "items": jtd_form_to_schema(&items_key_name, get_jtd_map(&items_key_name, elements_value)) })
+    } else if jtd_map.contains_key("properties") || jtd_map.contains_key("optionalProperties") {
+        let mut props_map: Map<String, Value> = Map::new();
+        let mut required: Vec<Value> = Vec::new();
+        // optional properties are Options left out when absent, so that they round-trip
+        let mut optional: Vec<Value> = Vec::new();
+        for props_keyword in ["properties", "optionalProperties"] {
+            if let Some(jtd_props_value) = jtd_map.get(props_keyword) {
+                let jtd_props_map: &Map<String, Value> = match jtd_props_value {
+                    Value::Object(jtd_props_map) => jtd_props_map,
+                    _                            => panic!("Could not parse JSON Type Definition, invalid {} for {}\n", props_keyword, key_name)
+                };
+                for (prop_name, prop_value) in jtd_props_map {
+                    props_map.insert(prop_name.to_string(), Value::Object(jtd_form_to_schema(prop_name, get_jtd_map(prop_name, prop_value))));
+                    if props_keyword == "properties" {
+                        required.push(Value::String(prop_name.to_string()));
+                    } else {
+                        optional.push(Value::String(prop_name.to_string()));
+                    }
+                }
+            }
+        }
+        let additional_properties: bool = jtd_map.get("additionalProperties") == Some(&Value::Bool(true));
+        json!({ "type": "object", "properties": props_map, "required": required, "additionalProperties": additional_properties, "x-rust-optional": optional })
+    } else if let Some(values_value) = jtd_map.get("values") {
+        let values_key_name: String = format!("{}{{}}", key_name);
+        json!({ "type": "object", "additionalProperties": jtd_form_to_schema(&values_key_name, get_jtd_map(&values_key_name, values_value)) })
+    } else if let Some(discriminator_value) = jtd_map.get("discriminator") {
+        let tag_name: &str = match discriminator_value {
+            Value::String(tag_name) => tag_name,
+            _                       => panic!("Could not parse JSON Type Definition, invalid discriminator for {}\n", key_name)
+        };
+        let mapping_map: &Map<String, Value> = match jtd_map.get("mapping") {
+            Some(Value::Object(mapping_map)) if !mapping_map.is_empty() => mapping_map,
+            _                                                           => panic!("Could not parse JSON Type Definition, invalid mapping for {}\n", key_name)
+        };
+        let mut branches: Vec<Value> = Vec::new();
+        for (tag_value, branch_value) in mapping_map {
+            let mut branch_map: Map<String, Value> = jtd_form_to_schema(tag_value, get_jtd_map(tag_value, branch_value));
+            if branch_map.get("type") != Some(&Value::String("object".to_string())) || branch_map.contains_key("anyOf") {
+                panic!("Could not parse JSON Type Definition, mapping {} for {} must be a non-nullable properties form\n", tag_value, key_name);
+            }
+            branch_map["properties"][tag_name] = json!({ "const": tag_value });
+            if let Some(Value::Array(required)) = branch_map.get_mut("required") {
+                required.insert(0, Value::String(tag_name.to_string()));
+            }
+            branches.push(Value::Object(branch_map));
+        }
+        json!({ "oneOf": branches })
+    } else {
+        // the empty form accepts any JSON
+        json!({ "x-rust-type": "serde_json::Value" })
+    };
+    let mut schema_map: Map<String, Value> = match schema_value {
+        Value::Object(schema_map) => schema_map,
+        _                         => unreachable!(),
+    };
+    if let Some(Value::String(description)) = jtd_map.get("metadata").and_then(|metadata| metadata.get("description")) {
+        schema_map.insert("description".to_string(), Value::String(description.to_string()));
+    }
+    if jtd_map.get("nullable") == Some(&Value::Bool(true)) {
+        let mut nullable_map: Map<String, Value> = Map::new();
+        nullable_map.insert("anyOf".to_string(), json!([schema_map, { "type": "null" }]));
+        return nullable_map;
+    }
+    schema_map
+}
+
+/// a JSON Type Definition schema, which must be a JSON object
+fn get_jtd_map<'a>(key_name: &str, jtd_value: &'a Value) -> &'a Map<String, Value> {
+    match jtd_value {
+        Value::Object(jtd_map) => jtd_map,
+        _                      => panic!("Could not parse JSON Type Definition, invalid schema for {}\n", key_name)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn process_jtd_forms() {
+        let contents: &str = r##"{
+            "definitions": {
+                "address": { "properties": { "street": { "type": "string" } } }
+            },
+            "properties": {
+                "id": { "type": "uint32" },
+                "name": { "type": "string" },
+                "created": { "type": "timestamp" },
+                "score": { "type": "float32" },
+                "home": { "ref": "address" },
+                "work": { "ref": "address", "nullable": true },
+                "status": { "enum": ["ACTIVE", "on_hold"] },
+                "tags": { "elements": { "type": "string" } },
+                "limits": { "values": { "type": "int16" } },
+                "extra": {}
+            },
+            "optionalProperties": {
+                "nickname": { "type": "string", "nullable": true },
+                "nick": { "type": "string" },
+                "office": { "ref": "address" }
+            }
+        }"##;
+        let mut options: GeneratorOptions = GeneratorOptions::default();
+        options.custom_name_map.insert("".to_string(), "User".to_string());
+        let ts = jtd_to_struct(contents, &options).unwrap();
+        print!("{}\r\n", ts);
+        assert!(ts.contains("pub struct User {"));
+        assert!(ts.contains("pub id: u32,"));
+        assert!(ts.contains("pub created: String,"));
+        assert!(ts.contains("pub score: f32,"));
+        assert!(ts.contains("pub home: Address,"));
+        assert!(ts.contains("pub work: Option<Address>,"));
+        assert!(ts.contains("    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n    pub nickname: Option<String>,"));
+        assert!(ts.contains("    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n    pub nick: Option<String>,"));
+        assert!(ts.contains("    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n    pub office: Option<Address>,"));
+        assert!(ts.contains("pub status: User_status,"));
+        assert!(ts.contains("pub enum User_status {\n    #[default]\n    #[serde(rename = \"ACTIVE\")]\n    Active,\n    #[serde(rename = \"on_hold\")]\n    OnHold,\n}"));
+        assert!(ts.contains("pub tags: Vec<String>,"));
+        assert!(ts.contains("pub limits: ::std::collections::HashMap<String, i16>,"));
+        assert!(ts.contains("pub extra: ::serde_json::Value,"));
+        assert!(ts.contains("pub struct Address {"));
+    }
+
+    #[test]
+    fn process_jtd_discriminator() {
+        let contents: &str = r##"{
+            "discriminator": "eventType",
+            "mapping": {
+                "USER_CREATED": { "properties": { "id": { "type": "string" } } },
+                "USER_DELETED": { "properties": { "id": { "type": "string" }, "softDelete": { "type": "boolean" } } }
+            }
+        }"##;
+        let mut options: GeneratorOptions = GeneratorOptions::default();
+        options.custom_name_map.insert("".to_string(), "Event".to_string());
+        let ts = jtd_to_struct(contents, &options).unwrap();
+        print!("{}\r\n", ts);
+        assert!(ts.contains("#[serde(tag = \"eventType\")]\r\npub enum Event {"));
+        assert!(ts.contains("    #[serde(rename = \"USER_CREATED\")]\n    UserCreated(Event_UserCreated),"));
+        assert!(ts.contains("    #[serde(rename = \"USER_DELETED\")]\n    UserDeleted(Event_UserDeleted),"));
+        assert!(ts.contains("pub struct Event_UserDeleted {"));
+        assert!(!ts.contains("pub eventType"));
+    }
+
+    #[test]
+    #[should_panic(expected = "unknown keyword")]
+    fn process_jtd_unknown_keyword() {
+        let _ = jtd_to_struct(r##"{ "type": "string", "format": "email" }"##, &GeneratorOptions::default());
+    }
+
+}
@@ -0,0 +1,30 @@
+//! Copyright (c) 2023  Ward van der Veer
+//!
+//! code generation behind the json_schema_parser macros, for use from build scripts and tools
+
+extern crate serde_json;
+extern crate regex;
+#[cfg(feature = "yaml")]
+extern crate serde_yaml;
+#[cfg(feature = "json5")]
+extern crate json5;
+
+pub mod draft;
+pub mod infer;
+pub mod jtd;
+pub mod lint;
+pub mod openapi;
+pub mod processor;
+pub mod source;
+pub mod validator;
+#[cfg(test)]
+mod compliance;
+
+pub use draft::Draft;
+pub use infer::{infer_schema, InferOptions};
+pub use jtd::{jtd_file_impl, jtd_to_json_schema, jtd_to_struct};
+pub use lint::{lint_schema, LintIssue};
+pub use openapi::{openapi_file_impl, openapi_to_json_schema_defs, openapi_to_struct};
+pub use processor::{json_schema_dir_impl, json_schema_file_impl, json_schema_file_pointer_impl, json_schema_defs_to_structs, json_schema_to_struct, json_schema_value_to_struct, Collection, GeneratorOptions};
+pub use source::{parse_document, SourceFormat};
+pub use validator::{validate, ValidationError};
@@ -0,0 +1,379 @@
+//! Copyright (c) 2023  Ward van der Veer
+
+extern crate serde_json;
+use draft::Draft;
+use processor::{format_struct_name, get_conditional_schemas, get_discriminator, get_nullable, get_string_enum};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use validator::{escape_pointer_token, resolve_pointer};
+
+/// keywords the generated types can't express, with what to do instead
+const UNSUPPORTED_KEYWORDS: &[(&str, &str)] = &[
+    ("not",                   "describe what is allowed rather than what is not"),
+    ("patternProperties",     "use additionalProperties, or declare the properties"),
+    ("unevaluatedProperties", "use additionalProperties"),
+    ("unevaluatedItems",      "use items"),
+    ("$dynamicRef",           "use $ref"),
+    ("$recursiveRef",         "use $ref"),
+];
+
+/// something in a schema the generator handles poorly, located by JSON Pointer into the schema
+#[derive(Clone, Debug, PartialEq)]
+pub struct LintIssue {
+    pub pointer: String,
+    pub message: String,
+    pub suggestion: String,
+}
+
+/// walk a JSON Schema reporting what the generator would reject or handle poorly,
+/// following the draft named by its $schema unless overridden
+pub fn lint_schema(schema: &Value, draft_override: Option<Draft>) -> Vec<LintIssue> {
+    let mut linter = Linter { root: schema, draft: Draft::detect(schema, draft_override), issues: Vec::new() };
+    if let Value::Object(schema_map) = schema {
+        if schema_map.get("title").and_then(|title| title.as_str()).map(|title| title.is_empty()) != Some(false) {
+            linter.push("", "the root schema has no title, which names its type", "add a \"title\", or name the type with \"->Name\"");
+        }
+    }
+    linter.lint_node(schema, "");
+    linter.lint_type_names();
+    linter.issues
+}
+
+/// the document being linted, and what has been found so far
+struct Linter<'a> {
+    root: &'a Value,
+    draft: Draft,
+    issues: Vec<LintIssue>,
+}
+
+impl<'a> Linter<'a> {
+    /// add an issue
+    fn push(&mut self, pointer: &str, message: &str, suggestion: &str) {
+        self.issues.push(LintIssue { pointer: pointer.to_string(), message: message.to_string(), suggestion: suggestion.to_string() });
+    }
+
+    /// check one (sub)schema and then the schemas inside it
+    fn lint_node(&mut self, schema: &'a Value, pointer: &str) {
+        let schema_map: &'a Map<String, Value> = match schema {
+            Value::Object(schema_map) => schema_map,
+            _                         => return,
+        };
+        if let Some(Value::String(reference)) = schema_map.get("$ref") {
+            self.lint_ref(reference, &format!("{}/$ref", pointer));
+        }
+        match schema_map.get("type") {
+            Some(Value::Array(_))                                                                   => {
+                self.push(&format!("{}/type", pointer), "a list of types has no Rust type",
+                    "use anyOf with a { \"type\": \"null\" } branch for a value that can be null, or a schema per type");
+            },
+            Some(Value::String(type_name)) if type_name == "integer" && !has_integer_range(schema_map) => {
+                self.push(pointer, "an integer without a format or bounds becomes an i32, which larger values overflow",
+                    "add \"format\": \"int64\", or a minimum and maximum");
+            },
+            _                                                                                       => {},
+        }
+        for (keyword, suggestion) in UNSUPPORTED_KEYWORDS {
+            if schema_map.contains_key(*keyword) {
+                self.push(&format!("{}/{}", pointer, escape_pointer_token(keyword)), &format!("{} is not reflected in the generated types", keyword), suggestion);
+            }
+        }
+        if let Some(Value::Array(all_of)) = schema_map.get("allOf") {
+            // an if/then/else in allOf is understood, other schemas in it are not merged
+            if all_of.iter().any(|subschema| subschema.get("if").is_none() || !self.draft.has_conditionals()) {
+                self.push(&format!("{}/allOf", pointer), "the schemas in allOf are not merged into the generated type",
+                    "declare the properties in one schema, or $ref a shared one");
+            }
+        }
+        if (schema_map.contains_key("oneOf") || schema_map.contains_key("anyOf")) && get_nullable(schema_map).is_none() && get_discriminator(schema_map).is_none() {
+            let union_keyword: &str = if schema_map.contains_key("oneOf") { "oneOf" } else { "anyOf" };
+            self.push(&format!("{}/{}", pointer, union_keyword), &format!("the branches of {} can't be told apart by a tag property, so it becomes a serde_json::Value", union_keyword),
+                "give each branch an object property with a distinct \"const\" value, e.g. \"kind\": { \"const\": \"circle\" }");
+        }
+        if let Some(Value::Object(props_map)) = schema_map.get("properties") {
+            let conditional_schemas: Vec<&Map<String, Value>> = get_conditional_schemas(schema_map, self.draft);
+            let is_declared = |required_name: &str| props_map.contains_key(required_name)
+                || conditional_schemas.iter().any(|conditional_schema| conditional_schema.get("properties").and_then(|props| props.get(required_name)).is_some());
+            if let Some(Value::Array(required_names)) = schema_map.get("required") {
+                for (position, required_name) in required_names.iter().enumerate() {
+                    if let Some(required_name) = required_name.as_str().filter(|required_name| !is_declared(required_name)) {
+                        self.push(&format!("{}/required/{}", pointer, position), &format!("{} is required but is not one of the properties, so the generated type can't hold it", required_name),
+                            &format!("declare {} under \"properties\", or take it out of \"required\"", required_name));
+                    }
+                }
+            }
+        }
+        for (keyword, keyword_value) in schema_map {
+            let keyword_pointer: String = format!("{}/{}", pointer, escape_pointer_token(keyword));
+            match (keyword.as_str(), keyword_value) {
+                ("properties" | "patternProperties" | "$defs" | "definitions" | "dependentSchemas" | "dependencies", Value::Object(subschemas)) => {
+                    for (key_name, subschema) in subschemas {
+                        self.lint_node(subschema, &format!("{}/{}", keyword_pointer, escape_pointer_token(key_name)));
+                    }
+                },
+                ("allOf" | "anyOf" | "oneOf" | "prefixItems" | "items", Value::Array(subschemas)) => {
+                    for (position, subschema) in subschemas.iter().enumerate() {
+                        self.lint_node(subschema, &format!("{}/{}", keyword_pointer, position));
+                    }
+                },
+                ("items" | "additionalItems" | "additionalProperties" | "not" | "if" | "then" | "else" | "contains", _) => {
+                    self.lint_node(keyword_value, &keyword_pointer);
+                },
+                _ => {},
+            }
+        }
+    }
+
+    /// check that a $ref points at a schema the generator can follow, i.e. one under $defs (or definitions)
+    fn lint_ref(&mut self, reference: &str, pointer: &str) {
+        let fragment: &str = match reference.strip_prefix('#') {
+            Some(fragment) => fragment,
+            None           => {
+                self.push(pointer, &format!("$ref {} is in another document, which is not read", reference),
+                    "use json_schema_dir! for a directory of schemas that refer to each other, or copy the schema into $defs");
+                return;
+            },
+        };
+        if resolve_pointer(self.root, fragment).is_none() {
+            self.push(pointer, &format!("$ref {} can't be resolved", reference), "point it at a schema in this document, e.g. #/$defs/name");
+            return;
+        }
+        // the defs under the other drafts' keyword are followed too
+        let def_name: Option<&str> = fragment.strip_prefix(&format!("/{}/", self.draft.defs_keyword()))
+            .or_else(|| fragment.strip_prefix(&format!("/{}/", self.draft.other_defs_keyword())));
+        if def_name.map(|def_name| def_name.contains('/')) != Some(false) {
+            self.push(pointer, &format!("$ref {} is not to a schema directly under {}, which are the only ones with a type of their own", reference, self.draft.defs_keyword()),
+                &format!("move the schema it refers to into {}", self.draft.defs_keyword()));
+        }
+    }
+
+    /// check that no two schemas give their types the same name, after case conversion
+    fn lint_type_names(&mut self) {
+        let mut type_names: Vec<(String, String)> = Vec::new();
+        if let Value::Object(schema_map) = self.root {
+            if let Some(title) = schema_map.get("title").and_then(|title| title.as_str()).filter(|title| !title.is_empty()) {
+                let title: String = format_struct_name(title, &HashMap::new());
+                get_embedded_type_names(&title, schema_map, "", &mut type_names);
+                type_names.insert(0, (title, "".to_string()));
+            }
+            for defs_keyword in [self.draft.defs_keyword(), self.draft.other_defs_keyword()] {
+                let defs_map: &Map<String, Value> = match schema_map.get(defs_keyword) {
+                    Some(Value::Object(defs_map)) => defs_map,
+                    _                             => continue,
+                };
+                for (def_name, defn_value) in defs_map.iter().filter(|(def_name, _)| !def_name.is_empty()) {
+                    let def_pointer: String = format!("/{}/{}", defs_keyword, escape_pointer_token(def_name));
+                    let type_name: String = format_struct_name(def_name, &HashMap::new());
+                    type_names.push((type_name.clone(), def_pointer.clone()));
+                    if let Value::Object(defn_map) = defn_value {
+                        get_embedded_type_names(&type_name, defn_map, &def_pointer, &mut type_names);
+                    }
+                }
+            }
+        }
+        let mut first_pointers: HashMap<&str, &str> = HashMap::new();
+        let mut collisions: Vec<(String, String, String)> = Vec::new();
+        for (type_name, pointer) in &type_names {
+            match first_pointers.get(type_name.as_str()) {
+                Some(first_pointer) => collisions.push((type_name.to_string(), pointer.to_string(), first_pointer.to_string())),
+                None                => { first_pointers.insert(type_name, pointer); },
+            }
+        }
+        for (type_name, pointer, first_pointer) in collisions {
+            self.push(&pointer, &format!("the type {} is also the type of #{}", type_name, first_pointer),
+                &format!("rename one of them, e.g. with \"{}->Other\" or a different title", type_name));
+        }
+    }
+}
+
+/// whether an integer schema says how large its values can be, by a format or by both bounds
+fn has_integer_range(schema_map: &Map<String, Value>) -> bool {
+    schema_map.contains_key("format") || (schema_map.contains_key("minimum") && schema_map.contains_key("maximum"))
+}
+
+/// the names of the types the generator makes for the objects, enums and unions embedded in a schema's properties
+fn get_embedded_type_names(parent_name: &str, schema_map: &Map<String, Value>, pointer: &str, type_names: &mut Vec<(String, String)>) {
+    if let Some(Value::Object(props_map)) = schema_map.get("properties") {
+        for (key_name, defn_value) in props_map {
+            let prop_pointer: String = format!("{}/properties/{}", pointer, escape_pointer_token(key_name));
+            let mut type_name: String = format!("{}_{}", parent_name, key_name);
+            let mut defn_map: &Map<String, Value> = match defn_value {
+                Value::Object(defn_map) => defn_map,
+                _                       => continue,
+            };
+            let mut defn_pointer: String = prop_pointer;
+            if defn_map.get("type") == Some(&Value::String("array".to_string())) {
+                match defn_map.get("items") {
+                    Some(Value::Object(items_map)) => {
+                        type_name = format!("{}_item", type_name);
+                        defn_pointer = format!("{}/items", defn_pointer);
+                        defn_map = items_map;
+                    },
+                    _                              => continue,
+                }
+            }
+            if defn_map.contains_key("$ref") {
+                continue;
+            }
+            if defn_map.contains_key("properties") || get_string_enum(defn_map).is_some() || get_discriminator(defn_map).is_some() {
+                type_names.push((type_name.clone(), defn_pointer.clone()));
+                get_embedded_type_names(&type_name, defn_map, &defn_pointer, type_names);
+            }
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn lint_text(schema: &str) -> Vec<LintIssue> {
+        lint_schema(&serde_json::from_str(schema).unwrap(), None)
+    }
+
+    fn has_issue(issues: &[LintIssue], pointer: &str, message_part: &str) -> bool {
+        issues.iter().any(|issue| issue.pointer == pointer && issue.message.contains(message_part))
+    }
+
+    #[test]
+    fn lint_clean_schema() {
+        // a 2020-12 schema, where the refs to definitions are followed all the same
+        let issues = lint_text(r##"{
+            "title": "order",
+            "type": "object",
+            "required": ["line"],
+            "properties": {
+                "line": { "$ref": "#/$defs/line" },
+                "note": { "anyOf": [{ "type": "string" }, { "type": "null" }] },
+                "customer": { "$ref": "#/definitions/customer" }
+            },
+            "$defs": {
+                "line": { "type": "object", "properties": { "quantity": { "type": "integer", "format": "int32" } } }
+            },
+            "definitions": {
+                "customer": { "type": "object", "properties": { "name": { "type": "string" } } }
+            }
+        }"##);
+        assert_eq!(issues, Vec::new());
+    }
+
+    #[test]
+    fn lint_other_defs_keyword() {
+        let issues = lint_text(r##"{
+            "title": "a",
+            "type": "object",
+            "properties": {
+                "b": { "$ref": "#/definitions/b" }
+            },
+            "definitions": {
+                "b": { "type": "object", "properties": { "name": { "type": "string" } } }
+            }
+        }"##);
+        assert_eq!(issues, Vec::new());
+        let issues = lint_text(r##"{
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "title": "a",
+            "type": "object",
+            "properties": {
+                "b": { "$ref": "#/$defs/b" },
+                "c": { "$ref": "#/definitions/c" }
+            },
+            "$defs": {
+                "b": { "type": "object", "properties": { "name": { "type": "string" } } }
+            },
+            "definitions": {
+                "c": { "type": "object", "properties": { "name": { "type": "string" } } },
+                "B": { "type": "object", "properties": { "name": { "type": "string" } } }
+            }
+        }"##);
+        assert_eq!(issues.len(), 1);
+        assert!(has_issue(&issues, "/$defs/b", "the type B is also the type of #/definitions/B") || has_issue(&issues, "/definitions/B", "the type B is also the type of #/$defs/b"));
+    }
+
+    #[test]
+    fn lint_schema_issues() {
+        let issues = lint_text(r##"{
+            "type": "object",
+            "required": ["id", "missing"],
+            "properties": {
+                "id": { "type": "integer" },
+                "size": { "type": "integer", "minimum": 0, "maximum": 10 },
+                "shape": { "oneOf": [{ "type": "string" }, { "type": "object" }] },
+                "nullable": { "anyOf": [{ "type": "string" }, { "type": "null" }] },
+                "tagged": { "oneOf": [
+                    { "properties": { "kind": { "const": "a" } } },
+                    { "properties": { "kind": { "const": "b" } } }
+                ] },
+                "parent": { "$ref": "#/$defs/user/properties/parent" },
+                "other": { "$ref": "other.json#/$defs/user" },
+                "lost": { "$ref": "#/$defs/nothing" },
+                "labels": { "type": "object", "patternProperties": { "^x-": { "type": "string" } } },
+                "merged": { "allOf": [{ "$ref": "#/$defs/user" }, { "$ref": "#/$defs/User" }] },
+                "note": { "type": ["string", "null"] }
+            },
+            "$defs": {
+                "user": { "type": "object", "properties": { "parent": { "type": "string" } } },
+                "User": { "type": "string" }
+            }
+        }"##);
+        for issue in &issues {
+            print!("{}: {} ({})\r\n", issue.pointer, issue.message, issue.suggestion);
+        }
+        assert!(has_issue(&issues, "", "no title"));
+        assert!(has_issue(&issues, "/required/1", "missing is required but is not one of the properties"));
+        assert!(!has_issue(&issues, "/required/0", ""));
+        assert!(has_issue(&issues, "/properties/id", "an integer without a format or bounds"));
+        assert!(!has_issue(&issues, "/properties/size", ""));
+        assert!(has_issue(&issues, "/properties/shape/oneOf", "can't be told apart"));
+        assert!(!has_issue(&issues, "/properties/nullable/anyOf", ""));
+        assert!(!has_issue(&issues, "/properties/tagged/oneOf", ""));
+        assert!(has_issue(&issues, "/properties/parent/$ref", "is not to a schema directly under $defs"));
+        assert!(has_issue(&issues, "/properties/other/$ref", "in another document"));
+        assert!(has_issue(&issues, "/properties/lost/$ref", "can't be resolved"));
+        assert!(has_issue(&issues, "/properties/labels/patternProperties", "patternProperties is not reflected"));
+        assert!(has_issue(&issues, "/properties/merged/allOf", "not merged"));
+        assert!(has_issue(&issues, "/$defs/User", "the type User is also the type of #/$defs/user"));
+        assert!(issues.contains(&LintIssue {
+            pointer: "/properties/note/type".to_string(),
+            message: "a list of types has no Rust type".to_string(),
+            suggestion: "use anyOf with a { \"type\": \"null\" } branch for a value that can be null, or a schema per type".to_string(),
+        }));
+    }
+
+    #[test]
+    fn lint_conditional_schemas() {
+        let issues = lint_text(r##"{
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "title": "payment",
+            "type": "object",
+            "required": ["kind"],
+            "properties": { "kind": { "type": "string", "enum": ["card", "cash"] } },
+            "allOf": [{ "if": { "properties": { "kind": { "const": "card" } } }, "then": { "required": ["card_number"], "properties": { "card_number": { "type": "string" } } } }]
+        }"##);
+        assert_eq!(issues, vec![]);
+    }
+
+    #[test]
+    fn lint_embedded_type_names() {
+        let issues = lint_text(r##"{
+            "title": "order",
+            "type": "object",
+            "properties": {
+                "line": { "type": "array", "items": { "type": "object", "properties": { "sku": { "type": "string" } } } }
+            },
+            "$defs": {
+                "order_line_item": { "type": "object", "properties": { "sku": { "type": "string" } } }
+            }
+        }"##);
+        assert!(has_issue(&issues, "/$defs/order_line_item", "the type Order_line_item is also the type of #/properties/line/items"));
+    }
+
+    #[test]
+    fn lint_nbformat_file() {
+        let contents: String = fs::read_to_string("../examples/nbformat.v4.schema.json").unwrap();
+        let issues = lint_schema(&serde_json::from_str(&contents).unwrap(), None);
+        assert!(has_issue(&issues, "/definitions/markdown_cell/properties/source/$ref", "#/definitions/misc/source is not to a schema directly under definitions"));
+        assert!(issues.iter().all(|issue| !issue.message.contains("can't be resolved")));
+    }
+}
@@ -0,0 +1,109 @@
+//! Copyright (c) 2023  Ward van der Veer
+//!
+//! command line tools around the json_schema_parser code generation
+//!
+//! json_schema_parser infer [--title <title>] [--output <file>] [--max-enum-values <n>] [--no-formats] <sample>...
+//! json_schema_parser lint [--schema <draft>] <schema>...
+
+extern crate json_schema_parser_core;
+extern crate serde_json;
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process;
+use serde_json::Value;
+use json_schema_parser_core::{infer_schema, lint_schema, parse_document, Draft, InferOptions, LintIssue, SourceFormat};
+
+const USAGE: &str = "usage: json_schema_parser infer [--title <title>] [--output <file>] [--max-enum-values <n>] [--no-formats] <sample>...
+       json_schema_parser lint [--schema <draft>] <schema>...";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.first().map(|command| command.as_str()) {
+        Some("infer") => infer_command(&args[1..]),
+        Some("lint")  => lint_command(&args[1..]),
+        _             => exit_with_error("expected a command"),
+    }
+}
+
+/// infer a JSON Schema from sample documents, each a JSON, YAML or JSON5 file, and write it out
+fn infer_command(args: &[String]) {
+    let mut options: InferOptions = InferOptions::default();
+    let mut output_path: Option<String> = None;
+    let mut sample_paths: Vec<String> = Vec::new();
+    let mut arg_iter = args.iter();
+    while let Some(arg) = arg_iter.next() {
+        match arg.as_str() {
+            "--title"           => options.title = Some(next_value(&mut arg_iter, arg)),
+            "--output"          => output_path = Some(next_value(&mut arg_iter, arg)),
+            "--max-enum-values" => options.max_enum_values = next_value(&mut arg_iter, arg).parse()
+                                       .unwrap_or_else(|_| exit_with_error("--max-enum-values needs a number")),
+            "--no-formats"      => options.detect_formats = false,
+            _ if arg.starts_with("--") => exit_with_error(&format!("unknown option {}", arg)),
+            _                   => sample_paths.push(arg.to_string()),
+        }
+    }
+    if sample_paths.is_empty() {
+        exit_with_error("expected at least one sample");
+    }
+    if options.title.is_none() {
+        // named after the first sample, e.g. "sample_data.json" gives "sample_data"
+        let file_name: String = Path::new(&sample_paths[0]).file_name().map(|file_name| file_name.to_string_lossy().to_string()).unwrap_or_default();
+        options.title = file_name.split('.').next().map(|title| title.to_string());
+    }
+    let samples: Vec<Value> = sample_paths.iter().map(|sample_path| {
+        let sample_text: String = fs::read_to_string(sample_path)
+            .unwrap_or_else(|_| exit_with_error(&format!("could not read sample {}", sample_path)));
+        parse_document(&sample_text, SourceFormat::from_path(sample_path), sample_path)
+    }).collect();
+    let schema_text: String = serde_json::to_string_pretty(&infer_schema(&samples, &options)).unwrap() + "\n";
+    match output_path {
+        Some(output_path) => fs::write(&output_path, schema_text)
+                                 .unwrap_or_else(|_| exit_with_error(&format!("could not write {}", output_path))),
+        None              => print!("{}", schema_text),
+    }
+}
+
+/// report what the generator would handle poorly in JSON Schema files, each a JSON, YAML or JSON5 file,
+/// exiting with 1 when anything is found
+fn lint_command(args: &[String]) {
+    let mut draft_override: Option<Draft> = None;
+    let mut schema_paths: Vec<String> = Vec::new();
+    let mut arg_iter = args.iter();
+    while let Some(arg) = arg_iter.next() {
+        match arg.as_str() {
+            "--schema"                 => draft_override = Some(Draft::from_name(&next_value(&mut arg_iter, arg))
+                                              .unwrap_or_else(|| exit_with_error("--schema needs a draft, e.g. draft-07 or 2020-12"))),
+            _ if arg.starts_with("--") => exit_with_error(&format!("unknown option {}", arg)),
+            _                          => schema_paths.push(arg.to_string()),
+        }
+    }
+    if schema_paths.is_empty() {
+        exit_with_error("expected at least one schema");
+    }
+    let mut issue_count: usize = 0;
+    for schema_path in &schema_paths {
+        let schema_text: String = fs::read_to_string(schema_path)
+            .unwrap_or_else(|_| exit_with_error(&format!("could not read schema {}", schema_path)));
+        let schema: Value = parse_document(&schema_text, SourceFormat::from_path(schema_path), schema_path);
+        let issues: Vec<LintIssue> = lint_schema(&schema, draft_override);
+        for issue in &issues {
+            println!("{}#{}: {}\n    {}", schema_path, issue.pointer, issue.message, issue.suggestion);
+        }
+        issue_count += issues.len();
+    }
+    if issue_count > 0 {
+        eprintln!("json_schema_parser: {} issue{} found", issue_count, if issue_count == 1 { "" } else { "s" });
+        process::exit(1);
+    }
+}
+
+/// the value following an option
+fn next_value<'a>(arg_iter: &mut impl Iterator<Item = &'a String>, option: &str) -> String {
+    arg_iter.next().cloned().unwrap_or_else(|| exit_with_error(&format!("{} needs a value", option)))
+}
+
+fn exit_with_error(message: &str) -> ! {
+    eprintln!("json_schema_parser: {}\n{}", message, USAGE);
+    process::exit(2);
+}
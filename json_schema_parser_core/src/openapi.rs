@@ -0,0 +1,554 @@
+//! Copyright (c) 2023  Ward van der Veer
+
+extern crate serde_json;
+use serde_json::{json, Map, Result, Value};
+use std::collections::{HashMap, HashSet};
+use processor::{format_variant_name, json_schema_defs_at_to_structs, GeneratorOptions};
+use source::{get_file_dependency_text, parse_document, read_schema_file, SourceFormat};
+
+/// prefix of refs to schemas under components
+const COMPONENTS_SCHEMAS_PREFIX: &str = "#/components/schemas/";
+
+/// operations a path item can hold
+const OPERATION_METHODS: &[&str] = &["get", "put", "post", "delete", "options", "head", "patch", "trace"];
+
+/// the components used as variants of a discriminator, as the generator expects them
+struct TaggedSchemas {
+    /// the components also referred to other than as a variant, which keep their tag
+    shared_names: HashSet<String>,
+    /// (variant schema name, component name, tag name) for each variant
+    variants: Vec<(String, String, String)>,
+}
+
+/// implementation of openapi_file macro code
+pub fn openapi_file_impl(file_path: String, options: &GeneratorOptions) -> String {
+    let (resolved_path, openapi_text): (String, String) = read_schema_file(&file_path, "OpenAPI");
+    let source_format: SourceFormat = options.source_format.unwrap_or_else(|| SourceFormat::from_path(&file_path));
+    let openapi_value: Value = parse_document(&openapi_text, source_format, &file_path);
+    json_schema_defs_at_to_structs(&openapi_to_json_schema_defs(&openapi_value, options), "/components/schemas", options) + &get_file_dependency_text(&resolved_path)
+}
+
+/// convert an OpenAPI 3.0 or 3.1 document in a string slice, JSON or YAML, to Rust types for its components/schemas,
+/// and for its operation bodies when chosen
+pub fn openapi_to_struct(openapi_text: &str, options: &GeneratorOptions) -> Result<String> {
+    let openapi_value: Value = if openapi_text.trim_start().starts_with('{') {
+        serde_json::from_str(openapi_text)?
+    } else {
+        parse_document(openapi_text, SourceFormat::Yaml, "OpenAPI document")
+    };
+    let defs_map: Map<String, Value> = openapi_to_json_schema_defs(&openapi_value, options);
+    Ok(json_schema_defs_at_to_structs(&defs_map, "/components/schemas", options))
+}
+
+/// the components/schemas of an OpenAPI document, and its operation bodies when chosen, as a draft 2020-12 $defs map
+pub fn openapi_to_json_schema_defs(openapi_value: &Value, options: &GeneratorOptions) -> Map<String, Value> {
+    let is_openapi_30: bool = match openapi_value.get("openapi").and_then(|version| version.as_str()) {
+        Some(version) if version.starts_with("3.0") => true,
+        Some(version) if version.starts_with("3.1") => false,
+        _                                           => panic!("Could not parse OpenAPI, only 3.0 and 3.1 documents are supported\n")
+    };
+    let mut defs_map: Map<String, Value> = Map::new();
+    let mut tagged_schemas: TaggedSchemas = TaggedSchemas { shared_names: get_shared_schema_names(openapi_value), variants: Vec::new() };
+    if let Some(schemas_value) = openapi_value.get("components").and_then(|components| components.get("schemas")) {
+        let schemas_map: &Map<String, Value> = match schemas_value {
+            Value::Object(schemas_map) => schemas_map,
+            _                          => panic!("Could not parse OpenAPI, invalid components/schemas\n")
+        };
+        for (schema_name, schema_value) in schemas_map {
+            defs_map.insert(schema_name.to_string(), openapi_schema_to_json_schema(schema_name, schema_value, is_openapi_30, &mut tagged_schemas));
+        }
+    }
+    add_variant_schemas(&mut defs_map, &tagged_schemas);
+    if options.operation_bodies {
+        tagged_schemas.variants.clear();
+        for (body_name, body_schema) in get_operation_bodies(openapi_value, is_openapi_30, &defs_map, &mut tagged_schemas) {
+            if defs_map.contains_key(&body_name) {
+                panic!("Could not parse OpenAPI, operation body {} has the name of a schema\n", body_name);
+            }
+            defs_map.insert(body_name, body_schema);
+        }
+        add_variant_schemas(&mut defs_map, &tagged_schemas);
+    }
+    defs_map
+}
+
+/// the tag is written by serde for the enum, so the types of its variants leave it out: a component that is also
+/// used on its own keeps it, and its variant is a copy without it
+fn add_variant_schemas(defs_map: &mut Map<String, Value>, tagged_schemas: &TaggedSchemas) {
+    let mut copy_names: HashSet<&str> = HashSet::new();
+    for (variant_name, schema_name, tag_name) in &tagged_schemas.variants {
+        if variant_name != schema_name && copy_names.insert(variant_name) {
+            if defs_map.contains_key(variant_name) {
+                panic!("Could not parse OpenAPI, the variant {} of {} has the name of a schema\n", variant_name, schema_name);
+            }
+            let mut variant_map: Map<String, Value> = match defs_map.get(schema_name) {
+                Some(Value::Object(schema_map)) => schema_map.clone(),
+                _                               => continue,
+            };
+            variant_map.insert("x-rust-pointer".to_string(), Value::String(format!("/components/schemas/{}", schema_name)));
+            defs_map.insert(variant_name.to_string(), Value::Object(variant_map));
+        }
+        if let Some(Value::Object(variant_map)) = defs_map.get_mut(variant_name) {
+            remove_property(variant_map, tag_name);
+        }
+    }
+}
+
+/// the component schemas referred to other than as a variant of a discriminator, e.g. as a property or a body
+fn get_shared_schema_names(openapi_value: &Value) -> HashSet<String> {
+    let mut ref_counts: HashMap<String, (usize, usize)> = HashMap::new();
+    count_schema_refs(openapi_value, &mut ref_counts);
+    ref_counts.into_iter().filter(|(_, (ref_count, variant_count))| ref_count > variant_count).map(|(schema_name, _)| schema_name).collect()
+}
+
+/// count the $refs to each component schema, in all and as a variant of a discriminator
+fn count_schema_refs(value: &Value, ref_counts: &mut HashMap<String, (usize, usize)>) {
+    match value {
+        Value::Object(value_map) => {
+            if let Some(schema_name) = value_map.get("$ref").and_then(|ref_name| ref_name.as_str()).and_then(|ref_name| ref_name.strip_prefix(COMPONENTS_SCHEMAS_PREFIX)) {
+                ref_counts.entry(schema_name.to_string()).or_default().0 += 1;
+            }
+            if value_map.contains_key("discriminator") {
+                for union_keyword in ["oneOf", "anyOf"] {
+                    if let Some(Value::Array(branches)) = value_map.get(union_keyword) {
+                        for schema_name in branches.iter().filter_map(|branch| branch.get("$ref")?.as_str()?.strip_prefix(COMPONENTS_SCHEMAS_PREFIX)) {
+                            ref_counts.entry(schema_name.to_string()).or_default().1 += 1;
+                        }
+                    }
+                }
+            }
+            for nested_value in value_map.values() {
+                count_schema_refs(nested_value, ref_counts);
+            }
+        },
+        Value::Array(values) => {
+            for nested_value in values {
+                count_schema_refs(nested_value, ref_counts);
+            }
+        },
+        _ => {},
+    }
+}
+
+/// convert an OpenAPI schema object to JSON Schema, noting the components used as variants of a discriminator
+fn openapi_schema_to_json_schema(key_name: &str, schema_value: &Value, is_openapi_30: bool, tagged_schemas: &mut TaggedSchemas) -> Value {
+    let schema_map: &Map<String, Value> = match schema_value {
+        Value::Object(schema_map) => schema_map,
+        Value::Bool(_)            => return schema_value.clone(),
+        _                         => panic!("Could not parse OpenAPI, invalid schema for {}\n", key_name)
+    };
+    let mut new_schema_map: Map<String, Value> = Map::new();
+    for (keyword, keyword_value) in schema_map {
+        let new_keyword_value: Value = match (keyword.as_str(), keyword_value) {
+            ("$ref", Value::String(ref_name)) => match ref_name.strip_prefix(COMPONENTS_SCHEMAS_PREFIX) {
+                Some(schema_name) => Value::String(format!("#/$defs/{}", schema_name)),
+                None              => panic!("Could not parse OpenAPI, unsupported $ref {} for {}\n", ref_name, key_name)
+            },
+            ("properties", Value::Object(props_map)) => Value::Object(props_map.iter()
+                .map(|(prop_name, prop_value)| (prop_name.to_string(), openapi_schema_to_json_schema(prop_name, prop_value, is_openapi_30, tagged_schemas)))
+                .collect()),
+            ("items", _) | ("additionalProperties", _) | ("not", _) => openapi_schema_to_json_schema(&format!("{}[]", key_name), keyword_value, is_openapi_30, tagged_schemas),
+            ("allOf", Value::Array(branches)) | ("anyOf", Value::Array(branches)) | ("oneOf", Value::Array(branches)) | ("prefixItems", Value::Array(branches)) => Value::Array(branches.iter().enumerate()
+                .map(|(position, branch)| openapi_schema_to_json_schema(&format!("{}[{}]", key_name, position), branch, is_openapi_30, tagged_schemas))
+                .collect()),
+            _ => keyword_value.clone(),
+        };
+        new_schema_map.insert(keyword.to_string(), new_keyword_value);
+    }
+    if let Some(discriminator_value) = schema_map.get("discriminator") {
+        add_discriminator_tags(key_name, &mut new_schema_map, discriminator_value, tagged_schemas);
+    }
+    if is_openapi_30 {
+        convert_exclusive_bound(&mut new_schema_map, "minimum", "exclusiveMinimum");
+        convert_exclusive_bound(&mut new_schema_map, "maximum", "exclusiveMaximum");
+    }
+    let is_nullable: bool = if is_openapi_30 {
+        new_schema_map.shift_remove("nullable") == Some(Value::Bool(true))
+    } else {
+        remove_null_type(&mut new_schema_map)
+    };
+    if !is_nullable {
+        return Value::Object(new_schema_map);
+    }
+    // a lone allOf is how OpenAPI 3.0 puts nullable next to a $ref
+    if let Some(Value::Array(branches)) = new_schema_map.get("allOf") {
+        if branches.len() == 1 && new_schema_map.len() == 1 {
+            return json!({ "anyOf": [branches[0], { "type": "null" }] });
+        }
+    }
+    json!({ "anyOf": [new_schema_map, { "type": "null" }] })
+}
+
+/// turn an OpenAPI 3.0 boolean exclusive bound, e.g. minimum: 0 with exclusiveMinimum: true, into the numeric form
+fn convert_exclusive_bound(schema_map: &mut Map<String, Value>, bound_keyword: &str, exclusive_keyword: &str) {
+    match schema_map.get(exclusive_keyword) {
+        Some(Value::Bool(true))  => match schema_map.shift_remove(bound_keyword) {
+            Some(bound) => { schema_map.insert(exclusive_keyword.to_string(), bound); },
+            None        => { schema_map.shift_remove(exclusive_keyword); },
+        },
+        Some(Value::Bool(false)) => { schema_map.shift_remove(exclusive_keyword); },
+        _                        => {},
+    }
+}
+
+/// take "null" out of an OpenAPI 3.1 type array, returning whether it was there
+fn remove_null_type(schema_map: &mut Map<String, Value>) -> bool {
+    let json_type_names: Vec<Value> = match schema_map.get("type") {
+        Some(Value::Array(json_type_names)) => json_type_names.clone(),
+        _                                   => return false,
+    };
+    let other_type_names: Vec<Value> = json_type_names.iter().filter(|json_type_name| json_type_name.as_str() != Some("null")).cloned().collect();
+    if other_type_names.len() == json_type_names.len() {
+        return false;
+    }
+    match other_type_names.len() {
+        0 => { schema_map.insert("type".to_string(), Value::String("null".to_string())); },
+        1 => { schema_map.insert("type".to_string(), other_type_names[0].clone()); },
+        _ => { schema_map.insert("type".to_string(), Value::Array(other_type_names)); },
+    }
+    true
+}
+
+/// give each $ref branch of a oneOf/anyOf with a discriminator a constant tag property, as the generator expects
+fn add_discriminator_tags(key_name: &str, schema_map: &mut Map<String, Value>, discriminator_value: &Value, tagged_schemas: &mut TaggedSchemas) {
+    let tag_name: String = match discriminator_value.get("propertyName") {
+        Some(Value::String(tag_name)) => tag_name.to_string(),
+        _                             => panic!("Could not parse OpenAPI, discriminator without propertyName for {}\n", key_name)
+    };
+    let mapping: Map<String, Value> = match discriminator_value.get("mapping") {
+        Some(Value::Object(mapping)) => mapping.clone(),
+        _                            => Map::new(),
+    };
+    let union_keyword: &str = if schema_map.contains_key("oneOf") { "oneOf" } else { "anyOf" };
+    let branches: &mut Vec<Value> = match schema_map.get_mut(union_keyword) {
+        Some(Value::Array(branches)) => branches,
+        _                            => return,
+    };
+    for branch in branches.iter_mut() {
+        let schema_name: String = match branch.get("$ref").and_then(|ref_name| ref_name.as_str()).and_then(|ref_name| ref_name.strip_prefix("#/$defs/")) {
+            Some(schema_name) => schema_name.to_string(),
+            None              => continue,
+        };
+        // a mapping value is either a ref or a schema name
+        let tag_value: String = mapping.iter()
+            .find(|(_, mapped)| mapped.as_str() == Some(&format!("{}{}", COMPONENTS_SCHEMAS_PREFIX, schema_name)) || mapped.as_str() == Some(&schema_name))
+            .map(|(tag_value, _)| tag_value.to_string())
+            .unwrap_or_else(|| schema_name.clone());
+        // a component also used on its own keeps its tag, and the enum holds a copy without it
+        let variant_name: String = if tagged_schemas.shared_names.contains(&schema_name) { format!("{}_{}", key_name, schema_name) } else { schema_name.clone() };
+        branch["$ref"] = Value::String(format!("#/$defs/{}", variant_name));
+        branch["properties"] = json!({ tag_name.clone(): { "const": tag_value } });
+        tagged_schemas.variants.push((variant_name, schema_name, tag_name.clone()));
+    }
+}
+
+/// the request and response body schemas of every operation, named after the operation,
+/// leaving readOnly properties out of requests and writeOnly properties out of responses
+fn get_operation_bodies(openapi_value: &Value, is_openapi_30: bool, defs_map: &Map<String, Value>, tagged_schemas: &mut TaggedSchemas) -> Vec<(String, Value)> {
+    let mut bodies: Vec<(String, Value)> = Vec::new();
+    let paths_map: &Map<String, Value> = match openapi_value.get("paths") {
+        Some(Value::Object(paths_map)) => paths_map,
+        _                              => return bodies,
+    };
+    for (path, path_item) in paths_map {
+        for method in OPERATION_METHODS {
+            let operation: &Value = match path_item.get(method) {
+                Some(operation) => operation,
+                None            => continue,
+            };
+            let operation_name: String = match operation.get("operationId") {
+                Some(Value::String(operation_id)) => format_variant_name(operation_id),
+                _                                 => format_variant_name(&format!("{} {}", method, path)),
+            };
+            if let Some(request_body) = operation.get("requestBody") {
+                let request_body = resolve_component(openapi_value, request_body, "requestBodies");
+                if let Some(body_schema) = get_json_body_schema(request_body) {
+                    let body_name: String = format!("{}Request", operation_name);
+                    let body_schema = openapi_schema_to_json_schema(&body_name, body_schema, is_openapi_30, tagged_schemas);
+                    bodies.push((body_name, get_one_way_schema(body_schema, "readOnly", defs_map)));
+                }
+            }
+            if let Some(Value::Object(responses_map)) = operation.get("responses") {
+                for (status_code, response) in responses_map {
+                    let response = resolve_component(openapi_value, response, "responses");
+                    if let Some(body_schema) = get_json_body_schema(response) {
+                        let body_name: String = format!("{}Response{}", operation_name, status_code[0..1].to_uppercase() + &status_code[1..]);
+                        let body_schema = openapi_schema_to_json_schema(&body_name, body_schema, is_openapi_30, tagged_schemas);
+                        bodies.push((body_name, get_one_way_schema(body_schema, "writeOnly", defs_map)));
+                    }
+                }
+            }
+        }
+    }
+    bodies
+}
+
+/// follow a $ref to a request body or response under components
+fn resolve_component<'a>(openapi_value: &'a Value, component_value: &'a Value, component_kind: &str) -> &'a Value {
+    match component_value.get("$ref").and_then(|ref_name| ref_name.as_str()) {
+        Some(ref_name) => {
+            let component_name: &str = ref_name.strip_prefix(&format!("#/components/{}/", component_kind))
+                .unwrap_or_else(|| panic!("Could not parse OpenAPI, unsupported $ref {}\n", ref_name));
+            openapi_value.get("components").and_then(|components| components.get(component_kind)).and_then(|components| components.get(component_name))
+                .unwrap_or_else(|| panic!("Could not parse OpenAPI, unknown $ref {}\n", ref_name))
+        },
+        None           => component_value,
+    }
+}
+
+/// the schema of the JSON content of a request body or response, if it has one
+fn get_json_body_schema(body_value: &Value) -> Option<&Value> {
+    let content_map: &Map<String, Value> = body_value.get("content")?.as_object()?;
+    let media_type: &Value = content_map.get("application/json")
+        .or_else(|| content_map.iter().find(|(media_type_name, _)| media_type_name.ends_with("+json")).map(|(_, media_type)| media_type))?;
+    media_type.get("schema")
+}
+
+/// a body schema without its one way (readOnly or writeOnly) properties, copying a referenced schema that has any
+fn get_one_way_schema(body_schema: Value, one_way_keyword: &str, defs_map: &Map<String, Value>) -> Value {
+    let mut body_map: Map<String, Value> = match body_schema {
+        Value::Object(body_map) => body_map,
+        _                       => return body_schema,
+    };
+    if body_map.len() == 1 {
+        if let Some(Value::String(ref_name)) = body_map.get("$ref") {
+            let referenced_map: Option<&Map<String, Value>> = ref_name.strip_prefix("#/$defs/").and_then(|schema_name| defs_map.get(schema_name)).and_then(|referenced| referenced.as_object());
+            match referenced_map {
+                Some(referenced_map) if has_one_way_property(referenced_map, one_way_keyword) => body_map = referenced_map.clone(),
+                _                                                                             => return Value::Object(body_map),
+            }
+        }
+    }
+    let one_way_names: Vec<String> = match body_map.get("properties") {
+        Some(Value::Object(props_map)) => props_map.iter()
+            .filter(|(_, prop_value)| prop_value.get(one_way_keyword) == Some(&Value::Bool(true)))
+            .map(|(prop_name, _)| prop_name.to_string())
+            .collect(),
+        _                              => Vec::new(),
+    };
+    for one_way_name in one_way_names {
+        remove_property(&mut body_map, &one_way_name);
+    }
+    Value::Object(body_map)
+}
+
+/// whether any property of a schema has readOnly or writeOnly set
+fn has_one_way_property(schema_map: &Map<String, Value>, one_way_keyword: &str) -> bool {
+    match schema_map.get("properties") {
+        Some(Value::Object(props_map)) => props_map.values().any(|prop_value| prop_value.get(one_way_keyword) == Some(&Value::Bool(true))),
+        _                              => false,
+    }
+}
+
+/// remove a property, and its entry in required
+fn remove_property(schema_map: &mut Map<String, Value>, prop_name: &str) {
+    if let Some(Value::Object(props_map)) = schema_map.get_mut("properties") {
+        props_map.shift_remove(prop_name);
+    }
+    if let Some(Value::Array(required)) = schema_map.get_mut("required") {
+        required.retain(|required_name| required_name.as_str() != Some(prop_name));
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn process_openapi_30_yaml() {
+        let contents: &str = r##"
+openapi: 3.0.3
+info: { title: Pets, version: "1" }
+paths:
+  /pets/{petId}:
+    get:
+      operationId: showPetById
+      responses:
+        200:
+          description: a pet
+          content:
+            application/json:
+              schema: { $ref: "#/components/schemas/Pet" }
+    put:
+      requestBody:
+        content:
+          application/json:
+            schema: { $ref: "#/components/schemas/Pet" }
+      responses:
+        default:
+          description: an error
+          content:
+            application/problem+json:
+              schema: { $ref: "#/components/schemas/Error" }
+components:
+  schemas:
+    Pet:
+      oneOf:
+        - $ref: "#/components/schemas/Cat"
+        - $ref: "#/components/schemas/Dog"
+      discriminator:
+        propertyName: petType
+        mapping:
+          cat: "#/components/schemas/Cat"
+    Cat:
+      type: object
+      properties:
+        petType: { type: string }
+        id: { type: integer, format: int64, readOnly: true }
+        owner: { allOf: [ { $ref: "#/components/schemas/Owner" } ], nullable: true }
+    Dog:
+      type: object
+      properties:
+        petType: { type: string }
+        name: { type: string, nullable: true }
+    Owner:
+      type: object
+      properties:
+        password: { type: string, writeOnly: true }
+    Error:
+      type: object
+      properties:
+        message: { type: string }
+"##;
+        let mut options: GeneratorOptions = GeneratorOptions::default();
+        let ts = openapi_to_struct(contents, &options).unwrap();
+        print!("{}\r\n", ts);
+        assert!(ts.contains("#[serde(tag = \"petType\")]\r\npub enum Pet {"));
+        assert!(ts.contains("    #[serde(rename = \"cat\")]\n    Cat(Cat),"));
+        assert!(ts.contains("    #[serde(rename = \"Dog\")]\n    Dog(Dog),"));
+        assert!(!ts.contains("pub petType"));
+        assert!(ts.contains("    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n    pub id: Option<i64>,"));
+        assert!(ts.contains("pub owner: Option<Owner>,"));
+        assert!(ts.contains("pub name: Option<String>,"));
+        assert!(ts.contains("    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n    pub password: Option<String>,"));
+        assert!(!ts.contains("ShowPetById"));
+
+        options.operation_bodies = true;
+        let ts = openapi_to_struct(contents, &options).unwrap();
+        print!("{}\r\n", ts);
+        assert!(ts.contains("pub struct ShowPetByIdResponse200(pub Pet);"));
+        assert!(ts.contains("pub struct PutPetsPetIdRequest(pub Pet);"));
+        assert!(ts.contains("pub struct PutPetsPetIdResponseDefault(pub Error);"));
+    }
+
+    #[test]
+    fn process_openapi_31_json() {
+        let contents: &str = r##"{
+            "openapi": "3.1.0",
+            "paths": {
+                "/users": {
+                    "post": {
+                        "operationId": "create_user",
+                        "requestBody": { "$ref": "#/components/requestBodies/NewUser" },
+                        "responses": { "201": { "content": { "application/json": { "schema": { "$ref": "#/components/schemas/User" } } } } }
+                    }
+                }
+            },
+            "components": {
+                "requestBodies": {
+                    "NewUser": { "content": { "application/json": { "schema": { "$ref": "#/components/schemas/User" } } } }
+                },
+                "schemas": {
+                    "User": {
+                        "type": "object",
+                        "properties": {
+                            "id": { "type": "integer", "readOnly": true },
+                            "nickname": { "type": ["string", "null"] },
+                            "password": { "type": "string", "writeOnly": true }
+                        }
+                    }
+                }
+            }
+        }"##;
+        let options: GeneratorOptions = GeneratorOptions { operation_bodies: true, ..GeneratorOptions::default() };
+        let ts = openapi_to_struct(contents, &options).unwrap();
+        print!("{}\r\n", ts);
+        assert!(ts.contains("pub struct User {"));
+        assert!(ts.contains("pub nickname: Option<String>,"));
+        assert!(ts.contains("pub struct CreateUserRequest {\n    #[serde(default)]\n    pub nickname: Option<String>,\n    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n    pub password: Option<String>,\n}"));
+        assert!(ts.contains("pub struct CreateUserResponse201 {\n    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n    pub id: Option<i32>,\n    #[serde(default)]\n    pub nickname: Option<String>,\n}"));
+    }
+
+    #[test]
+    fn process_openapi_shared_variant() {
+        // Cat is a variant of Pet, and also a body and a property on its own, where it keeps its petType
+        let contents: &str = r##"{
+            "openapi": "3.1.0",
+            "paths": {
+                "/cats/{id}": {
+                    "get": {
+                        "operationId": "get_cat",
+                        "responses": { "200": { "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Cat" } } } } }
+                    }
+                }
+            },
+            "components": {
+                "schemas": {
+                    "Pet": {
+                        "oneOf": [{ "$ref": "#/components/schemas/Cat" }, { "$ref": "#/components/schemas/Dog" }],
+                        "discriminator": { "propertyName": "petType", "mapping": { "cat": "#/components/schemas/Cat", "dog": "Dog" } }
+                    },
+                    "Cat": {
+                        "type": "object",
+                        "required": ["petType", "name"],
+                        "properties": { "petType": { "type": "string" }, "name": { "type": "string" } }
+                    },
+                    "Dog": {
+                        "type": "object",
+                        "required": ["petType"],
+                        "properties": { "petType": { "type": "string" }, "bark": { "type": "boolean" } }
+                    },
+                    "Owner": {
+                        "type": "object",
+                        "properties": { "cat": { "$ref": "#/components/schemas/Cat" } }
+                    }
+                }
+            }
+        }"##;
+        let options: GeneratorOptions = GeneratorOptions::default();
+        let ts = openapi_to_struct(contents, &options).unwrap();
+        print!("{}\r\n", ts);
+        assert!(ts.contains("    #[serde(rename = \"cat\")]\n    Cat(Pet_Cat),"));
+        assert!(ts.contains("    #[serde(rename = \"dog\")]\n    Dog(Dog),"));
+        assert!(ts.contains("pub struct Cat {\n    #[serde(default)]\n    pub petType: String,\n    #[serde(default)]\n    pub name: String,\n}"));
+        assert!(ts.contains("pub struct Pet_Cat {\n    #[serde(default)]\n    pub name: String,\n}"));
+        assert!(ts.contains("pub struct Dog {\n    #[serde(default)]\n    pub bark: bool,\n}"));
+        assert!(ts.contains("pub cat: Cat,"));
+
+        let options: GeneratorOptions = GeneratorOptions { operation_bodies: true, ..GeneratorOptions::default() };
+        let ts = openapi_to_struct(contents, &options).unwrap();
+        assert!(ts.contains("pub struct GetCatResponse200(pub Cat);"));
+    }
+
+    #[test]
+    fn process_openapi_30_exclusive_bounds() {
+        let contents: &str = r##"{
+            "openapi": "3.0.3",
+            "components": {
+                "schemas": {
+                    "Count": { "type": "integer", "minimum": 0, "exclusiveMinimum": true, "maximum": 10, "exclusiveMaximum": false },
+                    "Ratio": { "type": "number", "maximum": 1, "exclusiveMaximum": true, "exclusiveMinimum": false }
+                }
+            }
+        }"##;
+        let openapi_value: Value = serde_json::from_str(contents).unwrap();
+        let defs_map = openapi_to_json_schema_defs(&openapi_value, &GeneratorOptions::default());
+        assert_eq!(defs_map["Count"], json!({ "type": "integer", "exclusiveMinimum": 0, "maximum": 10 }));
+        assert_eq!(defs_map["Ratio"], json!({ "type": "number", "exclusiveMaximum": 1 }));
+        let ts = openapi_to_struct(contents, &GeneratorOptions::default()).unwrap();
+        print!("{}\r\n", ts);
+        assert!(ts.contains("if (value as f64) <= 0.0 {"));
+        assert!(ts.contains("must be greater than"));
+        assert!(ts.contains("if (value as f64) > 10.0 {"));
+        assert!(ts.contains("if (value as f64) >= 1.0 {"));
+    }
+
+    #[test]
+    #[should_panic(expected = "only 3.0 and 3.1")]
+    fn process_swagger_2() {
+        let _ = openapi_to_struct(r##"{ "swagger": "2.0" }"##, &GeneratorOptions::default());
+    }
+
+}
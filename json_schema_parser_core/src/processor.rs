@@ -0,0 +1,2999 @@
+//! Copyright (c) 2023  Ward van der Veer
+
+extern crate serde_json;
+use serde_json::{Result, Value, Map};
+use std::collections::{HashMap, HashSet};
+use regex::Regex;
+use draft::Draft;
+use std::fs;
+use std::path::Path;
+use source::{get_file_dependency_text, parse_document, read_schema_file, resolve_file_path, SourceFormat};
+use validator::validate_with_remotes;
+
+/// Rust collection an array schema maps to
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Collection {
+    #[default]
+    Vec,
+    BTreeSet,
+    HashSet,
+    Array,
+    NonEmpty,
+}
+
+impl Collection {
+    /// recognise a collection name as used for overrides, e.g. "BTreeSet", "hashset", "array", "non-empty"
+    pub fn from_name(name: &str) -> Option<Collection> {
+        match name.trim().to_lowercase().replace(['-', '_', ' '], "").as_str() {
+            "vec"      => Some(Collection::Vec),
+            "btreeset" => Some(Collection::BTreeSet),
+            "hashset"  => Some(Collection::HashSet),
+            "array"    => Some(Collection::Array),
+            "nonempty" => Some(Collection::NonEmpty),
+            _          => None,
+        }
+    }
+}
+
+/// settings for generating Rust code from a JSON Schema
+#[derive(Clone, Debug, Default)]
+pub struct GeneratorOptions {
+    /// "old_name" -> "new_name", "" names the root struct
+    pub custom_name_map: HashMap<String, String>,
+    /// "field" -> "rust type"
+    pub custom_type_map: HashMap<String, String>,
+    /// draft to use instead of the one named by $schema
+    pub draft_override: Option<Draft>,
+    /// collection for arrays with uniqueItems, BTreeSet or HashSet
+    pub unique_items: Collection,
+    /// collection for arrays with minItems == maxItems, Array
+    pub fixed_items: Collection,
+    /// collection for arrays with minItems >= 1, NonEmpty
+    pub min_items: Collection,
+    /// "field" -> collection, overriding the three above, "field[]" for nested arrays
+    pub custom_collection_map: HashMap<String, Collection>,
+    /// OpenAPI: also generate request and response body types for every operation
+    pub operation_bodies: bool,
+    /// format of schema files, instead of the one given by their extension
+    pub source_format: Option<SourceFormat>,
+    /// also give every type a json_schema() function returning the schema it was generated from
+    pub json_schema_fn: bool,
+    /// derives added to every type, e.g. "Debug", dropped from types with a field that can't have them
+    pub extra_derives: Vec<String>,
+    /// attributes added to every type, e.g. "#[serde(deny_unknown_fields)]"
+    pub extra_attributes: Vec<String>,
+    /// "Rust type" -> derives added to that type only
+    pub custom_derive_map: HashMap<String, Vec<String>>,
+    /// "Rust type" -> attributes added to that type only
+    pub custom_attribute_map: HashMap<String, Vec<String>>,
+    /// path to the serde crate instead of ::serde, for a renamed or re-exported serde
+    pub serde_crate: Option<String>,
+    /// path to the serde_json crate instead of ::serde_json
+    pub serde_json_crate: Option<String>,
+    /// module to put the generated types in, instead of the scope the macro is used in
+    pub module_name: Option<String>,
+    /// visibility of that module, or of the types without one: "pub" (the default), "pub(crate)", or "" for private
+    pub visibility: Option<String>,
+    /// JSON Pointers of the only schemas to generate, e.g. "/$defs/cell", along with the types they refer to,
+    /// or all of them when empty; "" is the root schema
+    pub root_pointers: Vec<String>,
+    /// also give every struct a builder, e.g. User::builder().email("a@example.com").build()
+    pub builders: bool,
+    /// path to the regex crate, e.g. "::regex", to check the patterns of constrained scalar newtypes
+    pub regex_crate: Option<String>,
+    /// type of objects without properties, "Map" (the default, serde_json::Map<String, Value>), "BTreeMap",
+    /// "HashMap", or any other type, e.g. "my_crate::Attributes"; a BTreeMap or HashMap is also the map of typed values
+    pub free_form_object: Option<String>,
+}
+
+impl GeneratorOptions {
+    /// the path generated code reaches serde by
+    pub fn serde_path(&self) -> &str {
+        self.serde_crate.as_deref().unwrap_or("::serde")
+    }
+
+    /// the path generated code reaches serde_json by
+    pub fn serde_json_path(&self) -> &str {
+        self.serde_json_crate.as_deref().unwrap_or("::serde_json")
+    }
+
+    /// the Rust type of an object without properties, whose properties can be anything
+    pub fn free_form_object_type(&self) -> String {
+        let serde_json: &str = self.serde_json_path();
+        match self.free_form_object.as_deref() {
+            None | Some("Map")  => format!("{0}::Map<String, {0}::Value>", serde_json),
+            Some("BTreeMap")    => format!("::std::collections::BTreeMap<String, {}::Value>", serde_json),
+            Some("HashMap")     => format!("::std::collections::HashMap<String, {}::Value>", serde_json),
+            Some(type_name)     => type_name.to_string(),
+        }
+    }
+
+    /// the Rust type of an object whose properties all have the given type: the map of free-form objects, or else,
+    /// as serde_json::Map and other types only hold their own values, a HashMap
+    pub fn typed_map_type(&self, value_type_name: &str) -> String {
+        match self.free_form_object.as_deref() {
+            Some("BTreeMap") => format!("::std::collections::BTreeMap<String, {}>", value_type_name),
+            _                => format!("::std::collections::HashMap<String, {}>", value_type_name),
+        }
+    }
+}
+
+/// implementation of json_schema_file macro code
+pub fn json_schema_file_impl(file_path: String, options: &GeneratorOptions) -> String {
+    let (resolved_path, schema_text): (String, String) = read_schema_file(&file_path, "JSON Schema");
+    let source_format: SourceFormat = options.source_format.unwrap_or_else(|| SourceFormat::from_path(&file_path));
+    let schema_json_value: Value = parse_document(&schema_text, source_format, &file_path);
+    let struct_text = json_schema_value_to_struct(schema_json_value, options);
+    match struct_text {
+        Ok(rslt)      => rslt + &get_file_dependency_text(&resolved_path),
+        Err(err_msg)   => panic!("Could not parse error {} from JSON Schema {}\n", err_msg, schema_text)
+    }
+}
+
+/// implementation of the json_schema attribute macro code for a single definition: the schema at the JSON Pointer
+/// in the file, e.g. "/$defs/User", named type_name or else after the last part of the pointer
+pub fn json_schema_file_pointer_impl(file_path: String, pointer: &str, type_name: Option<&str>, options: &GeneratorOptions) -> String {
+    let (resolved_path, schema_text) = read_schema_file(&file_path, "JSON Schema");
+    let source_format: SourceFormat = options.source_format.unwrap_or_else(|| SourceFormat::from_path(&file_path));
+    let schema_json_value: Value = parse_document(&schema_text, source_format, &file_path);
+    let draft: Draft = Draft::detect(&schema_json_value, options.draft_override);
+    let mut schema_json_map: Map<String, Value> = match schema_json_value.pointer(pointer) {
+        Some(Value::Object(schema_json_map)) => schema_json_map.clone(),
+        _                                    => panic!("Could not parse JSON Schema, no schema at {} in {}\n", pointer, file_path),
+    };
+    let source_title: Option<Value> = schema_json_map.get("title").cloned();
+    let type_name: String = match type_name {
+        Some(type_name) => type_name.to_string(),
+        None            => {
+            let pointer_name: String = pointer.rsplit('/').next().unwrap_or_default().replace("~1", "/").replace("~0", "~");
+            format_struct_name(&pointer_name, &options.custom_name_map)
+        },
+    };
+    let mut pointer_options: GeneratorOptions = options.clone();
+    pointer_options.custom_name_map.insert(type_name.to_string(), type_name.to_string());
+    schema_json_map.insert("title".to_string(), Value::String(type_name));
+    schema_json_map.insert("x-rust-pointer".to_string(), Value::String(pointer.to_string()));
+    match json_schema_map_to_struct(&schema_json_map, source_title.as_ref(), draft, &pointer_options) {
+        Ok(rust_types) => finish_rust_text(rust_types, options) + &get_file_dependency_text(&resolved_path),
+        Err(err_msg)   => panic!("Could not parse error {} from JSON Schema {}\n", err_msg, schema_text)
+    }
+}
+
+/// implementation of json_schema_dir macro code: a module per schema file in the directory whose name matches the pattern,
+/// e.g. "*.json", with $refs from one file to another going to the types of the other module
+pub fn json_schema_dir_impl(dir_path: String, file_pattern: &str, options: &GeneratorOptions) -> String {
+    let resolved_dir_path: String = resolve_file_path(&dir_path);
+    let file_name_regex: Regex = Regex::new(&format!("^{}$", file_pattern.split('*').map(|part| part.split('?').map(regex::escape).collect::<Vec<String>>().join(".")).collect::<Vec<String>>().join(".*")))
+        .unwrap_or_else(|_| panic!("Could not parse JSON Schema file pattern: {}\n", file_pattern));
+    let mut file_names: Vec<String> = fs::read_dir(&resolved_dir_path)
+        .unwrap_or_else(|err| panic!("Could not read JSON Schema directory: {} (tried {}: {})\n", dir_path, resolved_dir_path, err))
+        .filter_map(|dir_entry| dir_entry.ok())
+        .filter(|dir_entry| dir_entry.path().is_file())
+        .map(|dir_entry| dir_entry.file_name().to_string_lossy().to_string())
+        .filter(|file_name| file_name_regex.is_match(file_name))
+        .collect();
+    file_names.sort();
+    if file_names.is_empty() {
+        panic!("Could not find JSON Schema files matching {} in {} (tried {})\n", file_pattern, dir_path, resolved_dir_path);
+    }
+    // each file's module and root type, so that $refs from other files can name them
+    let mut schema_files: Vec<(String, String, String, Value)> = Vec::new();
+    for file_name in &file_names {
+        let file_path: String = Path::new(&resolved_dir_path).join(file_name).to_string_lossy().to_string();
+        let (_, schema_text) = read_schema_file(&file_path, "JSON Schema");
+        let source_format: SourceFormat = options.source_format.unwrap_or_else(|| SourceFormat::from_path(file_name));
+        let schema_json_value: Value = parse_document(&schema_text, source_format, &file_path);
+        let file_stem: &str = file_name.split('.').next().unwrap_or_default();
+        let module_name: String = get_module_name(file_stem);
+        if schema_files.iter().any(|(other_module_name, _, _, _)| *other_module_name == module_name) {
+            panic!("Could not parse JSON Schema, more than one file in {} gives the module {}\n", dir_path, module_name);
+        }
+        let root_name: String = match schema_json_value.get("title").and_then(|title| title.as_str()) {
+            Some(title) => format_struct_name(title, &options.custom_name_map),
+            None        => format_struct_name(&get_module_name(file_stem), &options.custom_name_map),
+        };
+        schema_files.push((module_name, file_path, root_name, schema_json_value));
+    }
+    let mut type_files: HashMap<String, String> = HashMap::new();
+    let mut rslt: String = "".to_string();
+    for (module_name, file_path, root_name, schema_json_value) in &schema_files {
+        let linked_schema: Value = link_schema_file_refs(schema_json_value.clone(), &schema_files, options);
+        let mut file_options: GeneratorOptions = GeneratorOptions { module_name: Some(module_name.to_string()), ..options.clone() };
+        file_options.custom_name_map.insert("".to_string(), root_name.to_string());
+        let rust_types: Vec<RustType> = match json_schema_value_to_types(linked_schema, &file_options) {
+            Ok(rust_types) => rust_types,
+            Err(err_msg)   => panic!("Could not parse error {} from JSON Schema {}\n", err_msg, file_path),
+        };
+        for rust_type in &rust_types {
+            if let Some(other_file_path) = type_files.insert(rust_type.name.to_string(), file_path.to_string()) {
+                panic!("Could not parse JSON Schema, the type {} is generated from both {} and {}\n", rust_type.name, other_file_path, file_path);
+            }
+        }
+        rslt = format!("{}{}{}\n", rslt, finish_rust_text(rust_types, &file_options), get_file_dependency_text(file_path));
+    }
+    rslt
+}
+
+/// a module name for a schema file, e.g. "user-profile" becomes user_profile
+fn get_module_name(file_stem: &str) -> String {
+    let module_name: String = file_stem.to_lowercase().chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect();
+    if module_name.is_empty() || module_name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{}", module_name)
+    } else {
+        module_name
+    }
+}
+
+/// replace the $refs to the other schema files, e.g. "address.json" or "address.json#/$defs/street", with the
+/// Rust types generated for them in their modules
+fn link_schema_file_refs(schema_value: Value, schema_files: &[(String, String, String, Value)], options: &GeneratorOptions) -> Value {
+    match schema_value {
+        Value::Object(schema_map) => {
+            if let Some(Value::String(ref_name)) = schema_map.get("$ref") {
+                if !ref_name.starts_with('#') {
+                    let (ref_file, ref_fragment) = ref_name.split_once('#').unwrap_or((ref_name, ""));
+                    let ref_file_name: &str = ref_file.rsplit('/').next().unwrap_or_default();
+                    let schema_file = schema_files.iter()
+                        .find(|(_, file_path, _, _)| Path::new(file_path).file_name().map(|file_name| file_name.to_string_lossy() == ref_file_name).unwrap_or(false))
+                        .unwrap_or_else(|| panic!("Could not parse JSON Schema, $ref {} is not to one of the schema files\n", ref_name));
+                    let (module_name, _, root_name, other_schema) = schema_file;
+                    let other_draft: Draft = Draft::detect(other_schema, options.draft_override);
+                    let type_name: String = match ref_fragment.strip_prefix(&format!("/{}/", other_draft.defs_keyword()))
+                        .or_else(|| ref_fragment.strip_prefix(&format!("/{}/", other_draft.other_defs_keyword()))) {
+                        _ if ref_fragment.is_empty() || ref_fragment == "/" => root_name.to_string(),
+                        Some(def_name) if !def_name.contains('/')            => format_struct_name(def_name, &options.custom_name_map),
+                        _                                                    => panic!("Could not parse JSON Schema, unknown $ref {}\n", ref_name),
+                    };
+                    let mut linked_map: Map<String, Value> = Map::new();
+                    linked_map.insert("x-rust-type".to_string(), Value::String(format!("super::{}::{}", module_name, type_name)));
+                    return Value::Object(linked_map);
+                }
+            }
+            Value::Object(schema_map.into_iter().map(|(keyword, keyword_value)| (keyword, link_schema_file_refs(keyword_value, schema_files, options))).collect())
+        },
+        Value::Array(schema_items) => Value::Array(schema_items.into_iter().map(|schema_item| link_schema_file_refs(schema_item, schema_files, options)).collect()),
+        _                          => schema_value,
+    }
+}
+
+/// convert JSON Schema in a string slice to a Rust struct,
+/// interpreting keywords per the draft named by $schema unless overridden
+pub fn json_schema_to_struct(schema_text: &str, options: &GeneratorOptions) -> Result<String> {
+    let schema_json_maybe: Result<Value> = serde_json::from_str(schema_text);
+    let schema_json_value: Value = match schema_json_maybe {
+        Ok(sj)        => sj,
+        Err(err_msg)  => panic!("Could not parse JSON error {}\n", err_msg)
+    };
+    json_schema_value_to_struct(schema_json_value, options)
+}
+
+/// convert JSON Schema already parsed to a serde JSON value to a Rust struct
+pub fn json_schema_value_to_struct(schema_json_value: Value, options: &GeneratorOptions) -> Result<String> {
+    json_schema_value_to_types(schema_json_value, options).map(|rust_types| finish_rust_text(rust_types, options))
+}
+
+/// the Rust types for JSON Schema already parsed to a serde JSON value, before the options that concern them all are applied
+fn json_schema_value_to_types(schema_json_value: Value, options: &GeneratorOptions) -> Result<Vec<RustType>> {
+    let draft: Draft = Draft::detect(&schema_json_value, options.draft_override);
+    let schema_json_value: Value = merge_other_defs(schema_json_value, draft);
+    let schema_json_map: Map<String, Value> = match schema_json_value {
+        Value::Object(obj)  => obj,
+        _                   => panic!("Could not parse JSON Schema not JSON Object\n")
+    };
+    if options.root_pointers.is_empty() {
+        return json_schema_map_to_struct(&schema_json_map, schema_json_map.get("title"), draft, options);
+    }
+    let defs_keyword: &str = draft.defs_keyword();
+    let defs_map: Map<String, Value> = match schema_json_map.get(defs_keyword) {
+        Some(Value::Object(defs_map)) => defs_map.clone(),
+        _                             => Map::new(),
+    };
+    let (is_root_selected, selected_defs_map) = get_selected_defs(&schema_json_map, &defs_map, &format!("/{}", defs_keyword), draft, options);
+    if !is_root_selected {
+        return Ok(process_defs(&Value::Object(selected_defs_map), &format!("/{}", defs_keyword), draft, options));
+    }
+    let mut selected_schema_json_map: Map<String, Value> = schema_json_map.clone();
+    if selected_defs_map.is_empty() {
+        selected_schema_json_map.shift_remove(defs_keyword);
+    } else if selected_schema_json_map.contains_key(defs_keyword) {
+        selected_schema_json_map.insert(defs_keyword.to_string(), Value::Object(selected_defs_map));
+    }
+    json_schema_map_to_struct(&selected_schema_json_map, schema_json_map.get("title"), draft, options)
+}
+
+/// move the defs under the other drafts' keyword, e.g. "definitions" in a 2020-12 schema, to those under the draft's own,
+/// pointing the $refs to them there, so that both are found; each def notes where in the document it came from
+fn merge_other_defs(schema_json_value: Value, draft: Draft) -> Value {
+    let mut schema_json_map: Map<String, Value> = match schema_json_value {
+        Value::Object(schema_json_map) => schema_json_map,
+        _                              => return schema_json_value,
+    };
+    let other_defs_map: Map<String, Value> = match schema_json_map.shift_remove(draft.other_defs_keyword()) {
+        Some(Value::Object(other_defs_map)) => other_defs_map,
+        Some(other_defs_value)              => {
+            schema_json_map.insert(draft.other_defs_keyword().to_string(), other_defs_value);
+            return Value::Object(schema_json_map);
+        },
+        None                                => return Value::Object(schema_json_map),
+    };
+    let mut defs_map: Map<String, Value> = match schema_json_map.shift_remove(draft.defs_keyword()) {
+        Some(Value::Object(defs_map)) => defs_map,
+        _                             => Map::new(),
+    };
+    for (def_name, mut defn_value) in other_defs_map {
+        if defs_map.contains_key(&def_name) {
+            panic!("Could not parse JSON Schema, {} is defined under both {} and {}\n", def_name, draft.defs_keyword(), draft.other_defs_keyword());
+        }
+        if let Value::Object(defn_map) = &mut defn_value {
+            if !defn_map.contains_key("x-rust-pointer") {
+                defn_map.insert("x-rust-pointer".to_string(), Value::String(format!("/{}/{}", draft.other_defs_keyword(), escape_pointer_token(&def_name))));
+            }
+        }
+        defs_map.insert(def_name, defn_value);
+    }
+    schema_json_map.insert(draft.defs_keyword().to_string(), Value::Object(defs_map));
+    move_other_def_refs(Value::Object(schema_json_map), draft)
+}
+
+/// point the $refs to the defs under the other drafts' keyword at the draft's own, see merge_other_defs
+fn move_other_def_refs(schema_value: Value, draft: Draft) -> Value {
+    let other_defs_prefix: String = format!("#/{}/", draft.other_defs_keyword());
+    match schema_value {
+        Value::Object(schema_map) => Value::Object(schema_map.into_iter().map(|(keyword, keyword_value)| match (keyword.as_str(), keyword_value) {
+            ("$ref", Value::String(ref_name)) if ref_name.starts_with(&other_defs_prefix) => {
+                (keyword, Value::String(format!("#/{}/{}", draft.defs_keyword(), &ref_name[other_defs_prefix.len()..])))
+            },
+            (_, keyword_value) => (keyword, move_other_def_refs(keyword_value, draft)),
+        }).collect()),
+        Value::Array(schema_values) => Value::Array(schema_values.into_iter().map(|schema_value| move_other_def_refs(schema_value, draft)).collect()),
+        _                           => schema_value,
+    }
+}
+
+/// whether the root schema is chosen by the root pointers, and the defs they choose, along with every def the chosen
+/// schemas refer to, in their order in the document
+fn get_selected_defs(schema_json_map: &Map<String, Value>, defs_map: &Map<String, Value>, defs_pointer: &str, draft: Draft, options: &GeneratorOptions) -> (bool, Map<String, Value>) {
+    let mut is_root_selected: bool = false;
+    let mut selected_def_names: HashSet<String> = HashSet::new();
+    let mut pointers: Vec<String> = options.root_pointers.iter().map(|root_pointer| root_pointer.trim().trim_start_matches('#').to_string()).collect();
+    while let Some(pointer) = pointers.pop() {
+        // $refs are to the defs by their place in the document, or as a $defs map, e.g. from OpenAPI
+        let def_pointer: Option<&str> = pointer.strip_prefix(&format!("{}/", defs_pointer))
+            .or_else(|| pointer.strip_prefix(&format!("/{}/", draft.defs_keyword())))
+            .or_else(|| pointer.strip_prefix(&format!("/{}/", draft.other_defs_keyword())));
+        let selected_schema: Value = if pointer.is_empty() || pointer == "/" {
+            if is_root_selected {
+                continue;
+            }
+            is_root_selected = true;
+            let mut root_map: Map<String, Value> = schema_json_map.clone();
+            root_map.shift_remove(draft.defs_keyword());
+            Value::Object(root_map)
+        } else if let Some(def_pointer) = def_pointer {
+            let def_name: String = def_pointer.split('/').next().unwrap_or_default().replace("~1", "/").replace("~0", "~");
+            if !defs_map.contains_key(&def_name) {
+                panic!("Could not parse JSON Schema, no definition at {}\n", pointer);
+            }
+            if !selected_def_names.insert(def_name.to_string()) {
+                continue;
+            }
+            defs_map[&def_name].clone()
+        } else {
+            panic!("Could not parse JSON Schema, root {} is not the schema or under {}\n", pointer, defs_pointer);
+        };
+        get_local_refs(&selected_schema, &mut pointers);
+    }
+    let selected_defs_map: Map<String, Value> = defs_map.iter()
+        .filter(|(def_name, _)| selected_def_names.contains(*def_name))
+        .map(|(def_name, def_value)| (def_name.to_string(), def_value.clone()))
+        .collect();
+    (is_root_selected, selected_defs_map)
+}
+
+/// the JSON Pointers of the $refs within the document, e.g. "/$defs/cell" for "#/$defs/cell"
+fn get_local_refs(schema_value: &Value, pointers: &mut Vec<String>) {
+    match schema_value {
+        Value::Object(schema_map) => {
+            for (keyword, keyword_value) in schema_map {
+                match (keyword.as_str(), keyword_value) {
+                    ("$ref", Value::String(ref_name)) if ref_name.starts_with('#') => pointers.push(ref_name[1..].to_string()),
+                    _                                                              => get_local_refs(keyword_value, pointers),
+                }
+            }
+        },
+        Value::Array(schema_items) => schema_items.iter().for_each(|schema_item| get_local_refs(schema_item, pointers)),
+        _                          => {},
+    }
+}
+
+/// convert a draft 2020-12 $defs map to Rust structs, without a root struct
+pub fn json_schema_defs_to_structs(defs_map: &Map<String, Value>, options: &GeneratorOptions) -> String {
+    json_schema_defs_at_to_structs(defs_map, "/$defs", options)
+}
+
+/// convert a draft 2020-12 $defs map to Rust structs, where the map is at defs_pointer in the document overrides refer to
+pub(crate) fn json_schema_defs_at_to_structs(defs_map: &Map<String, Value>, defs_pointer: &str, options: &GeneratorOptions) -> String {
+    if !options.root_pointers.is_empty() {
+        let (_, selected_defs_map) = get_selected_defs(&Map::new(), defs_map, defs_pointer, Draft::Draft202012, options);
+        return finish_rust_text(process_defs(&Value::Object(selected_defs_map), defs_pointer, Draft::Draft202012, options), options);
+    }
+    finish_rust_text(process_defs(&Value::Object(defs_map.clone()), defs_pointer, Draft::Draft202012, options), options)
+}
+
+/// apply the options that concern all the generated types together: derives, visibility and the module
+fn finish_rust_text(mut rust_types: Vec<RustType>, options: &GeneratorOptions) -> String {
+    apply_defaults(&mut rust_types);
+    apply_builders(&mut rust_types, options);
+    apply_derives(&mut rust_types, options);
+    let visibility: &str = options.visibility.as_deref().unwrap_or("pub");
+    // in a module the types are pub, and the module has the visibility
+    let type_visibility: &str = if options.module_name.is_some() { "pub" } else { visibility };
+    let rust_text: String = rust_types.iter().map(|rust_type| rust_type.to_text(type_visibility)).collect::<Vec<String>>().join("\n");
+    match &options.module_name {
+        Some(module_name) => {
+            // the module can use the same custom types as the scope around it
+            let module_visibility: String = if visibility.is_empty() { "".to_string() } else { format!("{} ", visibility) };
+            format!("{}mod {} {{\n#![allow(non_snake_case, non_camel_case_types)]\n#[allow(unused_imports)]\nuse super::*;\n\n{}\n}}\n", module_visibility, module_name, rust_text)
+        },
+        None => rust_text,
+    }
+}
+
+/// convert JSON Schema in a serde JSON map to Rust types: its own, then those of its $defs,
+/// source_title being the title in the schema rather than one added to name a $def
+fn json_schema_map_to_struct(schema_json_map_raw: &Map<String, Value>, source_title: Option<&Value>, draft: Draft, options: &GeneratorOptions) -> Result<Vec<RustType>> {
+    let title: String = if !schema_json_map_raw.contains_key("title") || schema_json_map_raw["title"].as_str().is_none() {
+        if options.custom_name_map.contains_key("") {
+            options.custom_name_map.get("").unwrap().to_string()
+        } else {
+            panic!("Could not parse JSON Schema, no title\n");
+        }
+    } else {
+        format_struct_name(schema_json_map_raw["title"].as_str().unwrap(), &options.custom_name_map)
+    };
+    // where the schema is in the document, for overrides given by JSON Pointer
+    let pointer: &str = schema_json_map_raw.get("x-rust-pointer").and_then(|pointer| pointer.as_str()).unwrap_or("");
+    let schema_json_map = process_embedded_objects_into_defs(&title, schema_json_map_raw, draft, options);
+    let mut builder_type: Option<RustType> = None;
+    let mut rust_type: RustType = if let Some((prefix_items, tail_items)) = get_positional_items(&schema_json_map, draft) {
+        get_tuple_struct_type(&title, &prefix_items, &tail_items, draft, options)
+    } else if let Some((tag_name, branches)) = get_discriminator(&schema_json_map) {
+        get_tagged_enum_type(&title, &tag_name, &branches, draft, options)
+    } else if let Some(enum_values) = get_string_enum(&schema_json_map) {
+        get_string_enum_type(&title, &enum_values, options)
+    } else if schema_json_map.get("type") == Some(&Value::String("array".to_string())) {
+        get_array_struct_type(&title, &schema_json_map, draft, options)
+    } else if !schema_json_map.contains_key("properties") && has_field_type(&schema_json_map) {
+        let type_name: String = get_field_type(&title, schema_json_map.clone(), draft, options);
+        let checks: Vec<(String, String)> = get_scalar_checks(&schema_json_map, &type_name, draft, options);
+        let mut newtype: RustType = if checks.is_empty() {
+            get_newtype_struct_type(&title, &type_name, options)
+        } else {
+            get_constrained_newtype_type(&title, &type_name, &checks, &schema_json_map, draft, options)
+        };
+        newtype.doc_text = get_unchecked_pattern_doc(&schema_json_map, &type_name, options);
+        newtype
+    } else {
+        if !schema_json_map.contains_key("properties") {
+            panic!("Could not parse JSON Schema, no properties\n");
+        }
+        let mut struct_type: RustType = RustType::new("struct", &title, &["Clone", "Serialize", "Deserialize", "Default"], options);
+        let mut fields: Vec<RustField> = Vec::new();
+        let props_value: Value = schema_json_map["properties"].clone();
+        let conditional_names: Vec<String> = get_conditional_names(&schema_json_map, draft);
+        // properties marked to be left out when absent, e.g. the optionalProperties of a JSON Type Definition
+        let left_out_names: Vec<&str> = match schema_json_map.get("x-rust-optional") {
+            Some(Value::Array(left_out_names)) => left_out_names.iter().filter_map(|left_out_name| left_out_name.as_str()).collect(),
+            _                                  => Vec::new(),
+        };
+        let mut builder_fields: Vec<BuilderField> = Vec::new();
+        if let Value::Object(props_map) = props_value {
+            for props_map_item in props_map.iter() {
+                let key_name = props_map_item.0.clone();
+                let defn_value = props_map_item.1.clone();
+                let field_pointer: String = format!("{}/properties/{}", pointer, escape_pointer_token(&key_name));
+                let is_conditional: bool = conditional_names.contains(&key_name) || left_out_names.contains(&key_name.as_str());
+                let (field_name, type_name, is_optional) = get_field_declaration(&key_name, &field_pointer, &defn_value, is_conditional, draft, options);
+                fields.push(RustField { field_name: field_name.to_string(), type_name: type_name.to_string(), is_optional, has_default: true });
+                if options.builders {
+                    let is_required: bool = !is_optional && get_required(&schema_json_map).contains(&key_name.as_str());
+                    builder_fields.push(BuilderField { field_name, type_name, is_required, default_value: defn_value.get("default").cloned() });
+                }
+            }
+        }
+        struct_type.field_types = fields.iter().map(|field| field.type_name.to_string()).collect();
+        struct_type.default_field_types = struct_type.field_types.clone();
+        struct_type.fields = Some(fields);
+        if options.builders {
+            builder_type = Some(get_builder_type(&title, builder_fields, options));
+        }
+        struct_type
+    };
+    if let Some(Value::String(helper_of)) = schema_json_map_raw.get("x-rust-helper-of") {
+        rust_type.doc_text = format!("/// part of {}, not a type of its own in the schema\r\n{}", helper_of, rust_type.doc_text);
+    }
+    if options.json_schema_fn {
+        rust_type.impls_text += &format!("\n{}", get_json_schema_fn_text(&title, &schema_json_map, source_title, pointer, draft, options));
+    }
+    let mut rust_types: Vec<RustType> = vec![rust_type];
+    rust_types.extend(builder_type);
+    if schema_json_map.contains_key(draft.defs_keyword()) {
+        rust_types.extend(process_defs(&schema_json_map[draft.defs_keyword()], &format!("{}/{}", pointer, draft.defs_keyword()), draft, options));
+    }
+    Ok(rust_types)
+}
+
+/// move embedded objects into the $defs (or definitions, before 2019-09)
+fn process_embedded_objects_into_defs(struct_name: &str, schema_json_map: &Map<String, Value>, draft: Draft, options: &GeneratorOptions) -> Map<String, Value> {
+    let mut new_defs: HashMap<String, Map<String, Value>> = HashMap::new();
+    let pointer: &str = schema_json_map.get("x-rust-pointer").and_then(|pointer| pointer.as_str()).unwrap_or("");
+    let mut revised_schema_json_map: Map<String, Value> = extract_embedded_objects(struct_name, "", pointer, schema_json_map, &mut new_defs, true, draft, options);
+    if ! new_defs.is_empty() {
+        let mut defs_map: Map<String,Value> = Map::new();
+        if revised_schema_json_map.contains_key(draft.defs_keyword()) {
+            if let Value::Object(old_defs_map) = &revised_schema_json_map[draft.defs_keyword()] {
+                for (old_def_name, old_def_obj) in old_defs_map {
+                    defs_map.insert(old_def_name.to_string(), old_def_obj.clone());
+                }
+            }
+        } 
+        // in the order they are first referred to, so that the types come out in the order of the schema
+        let mut new_def_names: Vec<String> = Vec::new();
+        get_embedded_defs_order(&Value::Object(revised_schema_json_map.clone()), draft, &new_defs, &mut new_def_names);
+        let mut unreferenced_def_names: Vec<&String> = new_defs.keys().filter(|new_def_name| !new_def_names.contains(new_def_name)).collect();
+        unreferenced_def_names.sort();
+        new_def_names.extend(unreferenced_def_names.into_iter().cloned());
+        for new_def_name in new_def_names {
+            let mut new_def_obj: Map<String, Value> = new_defs[&new_def_name].clone();
+            // noted so that the type says it is only part of another
+            new_def_obj.insert("x-rust-helper-of".to_string(), Value::String(struct_name.to_string()));
+            defs_map.insert(new_def_name, Value::Object(new_def_obj));
+        }
+        revised_schema_json_map.insert(draft.defs_keyword().to_string(), Value::Object(defs_map));  
+    }
+    revised_schema_json_map
+} 
+
+/// the names of the new defs in the order the schema first refers to them, including from other new defs
+fn get_embedded_defs_order(schema_value: &Value, draft: Draft, new_defs: &HashMap<String, Map<String, Value>>, new_def_names: &mut Vec<String>) {
+    match schema_value {
+        Value::Object(schema_map) => {
+            for (keyword, keyword_value) in schema_map {
+                let def_name: Option<&str> = keyword_value.as_str().and_then(|ref_name| ref_name.strip_prefix(&format!("#/{}/", draft.defs_keyword())));
+                match def_name {
+                    Some(def_name) if keyword == "$ref" && new_defs.contains_key(def_name) && !new_def_names.iter().any(|new_def_name| new_def_name == def_name) => {
+                        new_def_names.push(def_name.to_string());
+                        get_embedded_defs_order(&Value::Object(new_defs[def_name].clone()), draft, new_defs, new_def_names);
+                    },
+                    _ => get_embedded_defs_order(keyword_value, draft, new_defs, new_def_names),
+                }
+            }
+        },
+        Value::Array(schema_items) => {
+            for schema_item in schema_items {
+                get_embedded_defs_order(schema_item, draft, new_defs, new_def_names);
+            }
+        },
+        _ => {},
+    }
+}
+
+/// extract embedded objects, noting where in the document each one came from
+#[allow(clippy::too_many_arguments)]
+fn extract_embedded_objects(name_to_field: &str, key_name: &str, pointer: &str, schema_json_map_section: &Map<String, Value>, new_defs: & mut HashMap<String, Map<String, Value>>, is_root: bool, draft: Draft, options: &GeneratorOptions) -> Map<String, Value> {
+    if schema_json_map_section.contains_key("$ref") || schema_json_map_section.contains_key("x-rust-type") {
+        // a $ref gives the type, whatever its siblings say
+        return schema_json_map_section.clone();
+    }
+    let normalized_section: Map<String, Value> = normalize_conditionals(schema_json_map_section, draft);
+    let schema_json_map_section: &Map<String, Value> = &normalized_section;
+    if let Some((union_keyword, inner_position, inner_type)) = get_nullable(schema_json_map_section) {
+        // the null branch becomes an Option, the other branch is named as if it stood alone
+        let inner_name: String = if is_root { format!("{}_inner", name_to_field) } else { name_to_field.to_string() };
+        let mut new_schema_json_map_section = schema_json_map_section.clone();
+        new_schema_json_map_section[union_keyword][inner_position] = Value::Object(extract_embedded_objects(&inner_name, key_name, &format!("{}/{}/{}", pointer, union_keyword, inner_position), inner_type, new_defs, false, draft, options));
+        return new_schema_json_map_section;
+    }
+    if let Some((tag_name, branches)) = get_discriminator(schema_json_map_section) {
+        let mut new_branches: Vec<Value> = Vec::new();
+        let union_keyword: &str = if schema_json_map_section.contains_key("oneOf") { "oneOf" } else { "anyOf" };
+        for (branch_position, (tag_value, branch)) in branches.into_iter().enumerate() {
+            // the tag is written by serde, so the variant's own type leaves it out
+            let mut branch_type: Map<String, Value> = branch.clone();
+            if let Some(Value::Object(branch_props)) = branch_type.get_mut("properties") {
+                branch_props.shift_remove(&tag_name);
+            }
+            if let Some(Value::Array(branch_required)) = branch_type.get_mut("required") {
+                branch_required.retain(|required_name| required_name.as_str() != Some(tag_name.as_str()));
+            }
+            if !branch_type.contains_key("type") && !branch_type.contains_key("$ref") {
+                branch_type.insert("type".to_string(), Value::String("object".to_string()));
+            }
+            let variant_name: String = format!("{}_{}", name_to_field, format_variant_name(&tag_value));
+            let mut new_branch: Map<String, Value> = extract_embedded_objects(&variant_name, key_name, &format!("{}/{}/{}", pointer, union_keyword, branch_position), &branch_type, new_defs, false, draft, options);
+            let mut tag_props: Map<String, Value> = Map::new();
+            tag_props.insert(tag_name.clone(), serde_json::json!({ "const": tag_value }));
+            new_branch.insert("properties".to_string(), Value::Object(tag_props));
+            new_branches.push(Value::Object(new_branch));
+        }
+        let mut new_schema_json_map_section = schema_json_map_section.clone();
+        new_schema_json_map_section.insert(union_keyword.to_string(), Value::Array(new_branches));
+        if !is_root {
+            insert_embedded_def(new_defs, name_to_field, pointer, new_schema_json_map_section);
+            new_schema_json_map_section = Map::new();
+            new_schema_json_map_section.insert("$ref".to_string(), Value::String(format!("#/{}/{}", draft.defs_keyword(), name_to_field)));
+        }
+        return new_schema_json_map_section;
+    }
+    if get_string_enum(schema_json_map_section).is_some() {
+        if is_root {
+            return schema_json_map_section.clone();
+        }
+        insert_embedded_def(new_defs, name_to_field, pointer, schema_json_map_section.clone());
+        let mut new_schema_json_map_section = Map::new();
+        new_schema_json_map_section.insert("$ref".to_string(), Value::String(format!("#/{}/{}", draft.defs_keyword(), name_to_field)));
+        return new_schema_json_map_section;
+    }
+    if !schema_json_map_section.contains_key("type") {
+        return schema_json_map_section.clone();
+    }
+    if is_free_form_object(schema_json_map_section) {
+        return schema_json_map_section.clone();
+    }
+    if let Some(values_type) = get_map_values_schema(schema_json_map_section) {
+        let mut new_schema_json_map_section = schema_json_map_section.clone();
+        new_schema_json_map_section["additionalProperties"] = Value::Object(extract_embedded_objects(&format!("{}_value", name_to_field), &format!("{}{{}}", key_name), &format!("{}/additionalProperties", pointer), values_type, new_defs, false, draft, options));
+        return new_schema_json_map_section;
+    }
+
+    let section_type: &str = schema_json_map_section["type"].as_str().unwrap();
+      
+    if section_type != "object" && section_type != "array" {
+        // nothing to change
+        return schema_json_map_section.clone();
+    }
+    if section_type == "array" {
+        let array_name = format!("{}_item", name_to_field);
+        if let Some((prefix_items, tail_items)) = get_positional_items(schema_json_map_section, draft) {
+            let (prefix_keyword, tail_keyword) = if draft.has_prefix_items() { ("prefixItems", "items") } else { ("items", "additionalItems") };
+            let mut new_schema_json_map_section = schema_json_map_section.clone();
+            let new_prefix_items: Vec<Value> = prefix_items.iter().enumerate().map(|(position, prefix_item)| match prefix_item {
+                Value::Object(prefix_item_type) => Value::Object(extract_embedded_objects(&format!("{}_{}", name_to_field, position), &format!("{}[{}]", key_name, position), &format!("{}/{}/{}", pointer, prefix_keyword, position), prefix_item_type, new_defs, false, draft, options)),
+                _                               => prefix_item.clone(),
+            }).collect();
+            new_schema_json_map_section.insert(prefix_keyword.to_string(), Value::Array(new_prefix_items));
+            if let Value::Object(tail_items_type) = &tail_items {
+                new_schema_json_map_section.insert(tail_keyword.to_string(), Value::Object(extract_embedded_objects(&array_name, &format!("{}[]", key_name), &format!("{}/{}", pointer, tail_keyword), tail_items_type, new_defs, false, draft, options)));
+            }
+            if !is_root && tail_items != Value::Bool(false) {
+                // a tail needs a tuple struct with its own serde impls
+                insert_embedded_def(new_defs, name_to_field, pointer, new_schema_json_map_section);
+                new_schema_json_map_section = Map::new();
+                new_schema_json_map_section.insert("$ref".to_string(), Value::String(format!("#/{}/{}", draft.defs_keyword(), name_to_field)));
+            }
+            return new_schema_json_map_section;
+        }
+        if let Some(items_type) = get_array_items_schema(schema_json_map_section, draft) {
+            let mut new_schema_json_map_section = schema_json_map_section.clone();
+            new_schema_json_map_section["items"] = Value::Object(extract_embedded_objects(&array_name, &format!("{}[]", key_name), &format!("{}/items", pointer), items_type, new_defs, false, draft, options));
+            if !is_root && get_collection(key_name, schema_json_map_section, options) == Collection::NonEmpty {
+                // a non-empty array needs a wrapper struct, which keeps the choice in x-rust-collection
+                new_schema_json_map_section.insert("x-rust-collection".to_string(), Value::String("NonEmpty".to_string()));
+                insert_embedded_def(new_defs, name_to_field, pointer, new_schema_json_map_section);
+                new_schema_json_map_section = Map::new();
+                new_schema_json_map_section.insert("$ref".to_string(), Value::String(format!("#/{}/{}", draft.defs_keyword(), name_to_field)));
+            }
+            return new_schema_json_map_section;
+        } else {
+            panic!("Can't find item type for {}", array_name);
+        }
+    }
+    let props_value: Value = schema_json_map_section["properties"].clone();
+    let mut new_schema_json_map_section = schema_json_map_section.clone();
+    if let Value::Object(props_map) = props_value {
+        for props_map_item in props_map.iter() {
+            let key_name = props_map_item.0.clone();
+            let defn_value = props_map_item.1.clone();
+            let obj_name = format!("{}_{}", name_to_field, key_name);
+            if let Value::Object(obj_type) = defn_value {
+                new_schema_json_map_section["properties"][&key_name] = Value::Object(extract_embedded_objects(&obj_name, &key_name, &format!("{}/properties/{}", pointer, escape_pointer_token(&key_name)), &obj_type, new_defs, false, draft, options));
+            } else {
+                panic!("Can find item type for {}", obj_name);
+            }
+        }
+    }
+    if !is_root {
+        insert_embedded_def(new_defs, name_to_field, pointer, new_schema_json_map_section.clone());
+        new_schema_json_map_section = Map::new();
+        new_schema_json_map_section.insert("$ref".to_string(), Value::String(format!("#/{}/{}", draft.defs_keyword(), name_to_field)));
+    }
+    new_schema_json_map_section
+}
+
+/// the schema with its conditional keywords turned into ones the generator understands: an if/then/else telling
+/// objects apart by the const value of an enum property becomes a oneOf with a branch per value, as for a tagged
+/// enum, and otherwise the properties only some objects have (from then, else, dependentSchemas or dependencies)
+/// join the others, see get_conditional_names
+fn normalize_conditionals(defn_map: &Map<String, Value>, draft: Draft) -> Map<String, Value> {
+    if let Some(tagged_map) = get_conditional_union(defn_map, draft) {
+        return tagged_map;
+    }
+    let mut props_map: Map<String, Value> = match defn_map.get("properties") {
+        Some(Value::Object(props_map)) => props_map.clone(),
+        _                              => return defn_map.clone(),
+    };
+    for conditional_schema in get_conditional_schemas(defn_map, draft) {
+        if let Some(Value::Object(conditional_props_map)) = conditional_schema.get("properties") {
+            for (key_name, defn_value) in conditional_props_map {
+                if !props_map.contains_key(key_name) {
+                    props_map.insert(key_name.to_string(), defn_value.clone());
+                }
+            }
+        }
+    }
+    let mut new_defn_map: Map<String, Value> = defn_map.clone();
+    new_defn_map.insert("properties".to_string(), Value::Object(props_map));
+    new_defn_map
+}
+
+/// the if/then/else schemas of an object, on their own or in allOf, with the ifs that choose them
+fn get_conditionals(defn_map: &Map<String, Value>, draft: Draft) -> Vec<&Map<String, Value>> {
+    if !draft.has_conditionals() {
+        return Vec::new();
+    }
+    let mut conditionals: Vec<&Map<String, Value>> = Vec::new();
+    if defn_map.contains_key("if") {
+        conditionals.push(defn_map);
+    }
+    if let Some(Value::Array(all_of)) = defn_map.get("allOf") {
+        conditionals.extend(all_of.iter().filter_map(|subschema| subschema.as_object()).filter(|subschema| subschema.contains_key("if")));
+    }
+    conditionals
+}
+
+/// the schemas an object must also match under some condition: then, else, and dependentSchemas (or the schemas in
+/// dependencies, before 2019-09)
+pub(crate) fn get_conditional_schemas(defn_map: &Map<String, Value>, draft: Draft) -> Vec<&Map<String, Value>> {
+    let mut conditional_schemas: Vec<&Map<String, Value>> = Vec::new();
+    for conditional in get_conditionals(defn_map, draft) {
+        conditional_schemas.extend(["then", "else"].iter().filter_map(|keyword| conditional.get(*keyword)?.as_object()));
+    }
+    let dependencies_keyword: &str = if draft.has_split_dependencies() { "dependentSchemas" } else { "dependencies" };
+    if let Some(Value::Object(dependencies)) = defn_map.get(dependencies_keyword) {
+        conditional_schemas.extend(dependencies.values().filter_map(|dependency| dependency.as_object()));
+    }
+    conditional_schemas
+}
+
+/// the properties that are not required but that decide, or are decided by, what else an object must have:
+/// the names in dependentRequired and dependentSchemas (or dependencies), and the properties then and else
+/// declare or require
+fn get_conditional_names(defn_map: &Map<String, Value>, draft: Draft) -> Vec<String> {
+    let mut conditional_names: Vec<String> = Vec::new();
+    let dependencies_keywords: &[&str] = if draft.has_split_dependencies() { &["dependentRequired", "dependentSchemas"] } else { &["dependencies"] };
+    for dependencies_keyword in dependencies_keywords {
+        if let Some(Value::Object(dependencies)) = defn_map.get(*dependencies_keyword) {
+            for (trigger_name, dependency) in dependencies {
+                conditional_names.push(trigger_name.to_string());
+                if let Value::Array(required_names) = dependency {
+                    conditional_names.extend(required_names.iter().filter_map(|required_name| required_name.as_str()).map(|required_name| required_name.to_string()));
+                }
+            }
+        }
+    }
+    for conditional_schema in get_conditional_schemas(defn_map, draft) {
+        conditional_names.extend(get_required(conditional_schema).into_iter().map(|required_name| required_name.to_string()));
+        if let Some(Value::Object(conditional_props_map)) = conditional_schema.get("properties") {
+            conditional_names.extend(conditional_props_map.keys().cloned());
+        }
+    }
+    let required_names: Vec<&str> = get_required(defn_map);
+    conditional_names.retain(|conditional_name| !required_names.contains(&conditional_name.as_str()));
+    conditional_names
+}
+
+/// the property and value an if schema tests for, when it only tests that a property has a const string value
+fn get_if_tag(if_value: &Value) -> Option<(String, String)> {
+    let if_map: &Map<String, Value> = if_value.as_object()?;
+    if if_map.keys().any(|keyword| !["properties", "required", "type"].contains(&keyword.as_str())) {
+        return None;
+    }
+    let props_map: &Map<String, Value> = if_map.get("properties")?.as_object()?;
+    if props_map.len() != 1 {
+        return None;
+    }
+    let (tag_name, tag_type) = props_map.iter().next()?;
+    let tag_value: &str = match (tag_type.get("const"), tag_type.get("enum")) {
+        (Some(Value::String(tag_value)), _)                               => tag_value,
+        (None, Some(Value::Array(tag_values))) if tag_values.len() == 1 => tag_values[0].as_str()?,
+        _                                                                 => return None,
+    };
+    Some((tag_name.to_string(), tag_value.to_string()))
+}
+
+/// the keywords that make some properties depend on others, in any draft
+const DEPENDENCY_KEYWORDS: &[&str] = &["dependentRequired", "dependentSchemas", "dependencies"];
+
+/// an object whose if/then/else schemas tell it apart by the value of one enum property, as a oneOf with a branch
+/// per value of the enum, holding the object's properties along with those of the then or else that value picks
+fn get_conditional_union(defn_map: &Map<String, Value>, draft: Draft) -> Option<Map<String, Value>> {
+    let conditionals: Vec<&Map<String, Value>> = get_conditionals(defn_map, draft);
+    if conditionals.is_empty() || defn_map.contains_key("oneOf") || defn_map.contains_key("anyOf") {
+        return None;
+    }
+    let if_tags: Vec<(String, String)> = conditionals.iter().map(|conditional| get_if_tag(&conditional["if"])).collect::<Option<Vec<(String, String)>>>()?;
+    let tag_name: &str = &if_tags[0].0;
+    if if_tags.iter().any(|(if_tag_name, _)| if_tag_name != tag_name) {
+        return None;
+    }
+    let props_map: &Map<String, Value> = defn_map.get("properties")?.as_object()?;
+    // the enum gives every value the tag can have, so that each can have a variant
+    let tag_values: Vec<String> = get_string_enum(props_map.get(tag_name)?.as_object()?)?;
+    if if_tags.iter().any(|(_, if_tag_value)| !tag_values.contains(if_tag_value)) {
+        return None;
+    }
+    let mut branches: Vec<Value> = Vec::new();
+    for tag_value in &tag_values {
+        let mut branch_props_map: Map<String, Value> = props_map.clone();
+        let mut branch_required: Vec<Value> = get_required(defn_map).into_iter().map(|required_name| Value::String(required_name.to_string())).collect();
+        for (conditional, (_, if_tag_value)) in conditionals.iter().zip(if_tags.iter()) {
+            let chosen_keyword: &str = if if_tag_value == tag_value { "then" } else { "else" };
+            if let Some(Value::Object(chosen_schema)) = conditional.get(chosen_keyword) {
+                if let Some(Value::Object(chosen_props_map)) = chosen_schema.get("properties") {
+                    for (key_name, defn_value) in chosen_props_map {
+                        branch_props_map.insert(key_name.to_string(), defn_value.clone());
+                    }
+                }
+                for required_name in get_required(chosen_schema) {
+                    if !branch_required.contains(&Value::String(required_name.to_string())) {
+                        branch_required.push(Value::String(required_name.to_string()));
+                    }
+                }
+            }
+        }
+        branch_props_map.insert(tag_name.to_string(), serde_json::json!({ "const": tag_value }));
+        let mut branch: Map<String, Value> = Map::new();
+        branch.insert("type".to_string(), Value::String("object".to_string()));
+        branch.insert("properties".to_string(), Value::Object(branch_props_map));
+        branch.insert("required".to_string(), Value::Array(branch_required));
+        // what some properties need of others holds whichever branch the object is
+        for keyword in DEPENDENCY_KEYWORDS {
+            if let Some(dependencies) = defn_map.get(*keyword) {
+                branch.insert(keyword.to_string(), dependencies.clone());
+            }
+        }
+        branches.push(Value::Object(branch));
+    }
+    let mut tagged_map: Map<String, Value> = defn_map.clone();
+    for keyword in ["if", "then", "else", "properties", "required"].iter().chain(DEPENDENCY_KEYWORDS) {
+        tagged_map.shift_remove(*keyword);
+    }
+    if let Some(Value::Array(all_of)) = tagged_map.get_mut("allOf") {
+        all_of.retain(|subschema| subschema.get("if").is_none());
+        if all_of.is_empty() {
+            tagged_map.shift_remove("allOf");
+        }
+    }
+    tagged_map.insert("oneOf".to_string(), Value::Array(branches));
+    Some(tagged_map)
+}
+
+/// add an embedded object to the new defs, noting where it was in the document
+fn insert_embedded_def(new_defs: &mut HashMap<String, Map<String, Value>>, def_name: &str, pointer: &str, mut def_map: Map<String, Value>) {
+    def_map.insert("x-rust-pointer".to_string(), Value::String(pointer.to_string()));
+    new_defs.insert(def_name.to_string(), def_map);
+}
+
+/// escape a key for use in a JSON Pointer (RFC 6901)
+fn escape_pointer_token(key_name: &str) -> String {
+    key_name.replace('~', "~0").replace('/', "~1")
+}
+
+/// process the $defs (or definitions) field, found at defs_pointer in the document
+fn process_defs(defs_value: &Value, defs_pointer: &str, draft: Draft, options: &GeneratorOptions) -> Vec<RustType> {
+    let mut rslt: Vec<RustType> = Vec::new();
+    if let Value::Object(defs_map) = defs_value {
+        for defs_map_item in defs_map.iter() {
+            let key_name = defs_map_item.0;
+            let defn_value = defs_map_item.1;
+            if let Value::Object(defn_map) = &defn_value {
+                let mut defn_map_mut = defn_map.clone();
+                defn_map_mut.insert("title".to_string(), Value::String(key_name.to_string()));
+                if !defn_map_mut.contains_key("x-rust-pointer") {
+                    defn_map_mut.insert("x-rust-pointer".to_string(), Value::String(format!("{}/{}", defs_pointer, escape_pointer_token(key_name))));
+                }
+                let this_def: Result<Vec<RustType>> = json_schema_map_to_struct(&defn_map_mut, defn_map.get("title"), draft, options);
+                if let Ok(this_ok) = this_def {
+                    rslt.extend(this_ok);
+                } else {
+                    panic!("Could not parse JSON Schema, invalid $def {}\n", key_name);
+                }
+            } else {
+                panic!("Could not parse JSON Schema, invalid $def {}\n", key_name);
+            }
+
+        }
+        return rslt;
+    }
+    panic!("Could not parse JSON Schema, invalid {}", draft.defs_keyword());
+}
+
+/// field types that stop a derive, which every field must also have
+const DERIVE_BLOCKERS: &[(&str, &[&str])] = &[
+    ("Eq",         &["f32", "f64"]),
+    ("Hash",       &["f32", "f64", "HashMap", "HashSet"]),
+    ("PartialOrd", &["HashMap", "HashSet", "Value", "Map"]),
+    ("Ord",        &["f32", "f64", "HashMap", "HashSet", "Value", "Map"]),
+    ("Copy",       &["String", "Vec", "HashMap", "HashSet", "BTreeSet", "BTreeMap", "Value", "Map", "Box"]),
+];
+
+/// the name of a derive without its path, e.g. "Serialize" for "::serde::Serialize"
+fn get_derive_name(derive: &str) -> &str {
+    derive.rsplit("::").next().unwrap_or(derive)
+}
+
+/// a generated Rust type, kept apart from the others until the options that concern them all, such as the extra
+/// derives and the visibility, are applied
+struct RustType {
+    name: String,
+    /// "struct" or "enum"
+    kind: &'static str,
+    /// doc comments before the type
+    doc_text: String,
+    /// the derives, by path, e.g. ::serde::Serialize
+    derives: Vec<String>,
+    /// the attributes after the derives, e.g. #[serde(transparent)]
+    attributes: Vec<String>,
+    /// the declaration after the name, e.g. "(pub String);\n", unless it has named fields
+    body_text: String,
+    /// its named fields, for a struct that has them
+    fields: Option<Vec<RustField>>,
+    /// the Rust types of its fields, or of what its variants hold
+    field_types: Vec<String>,
+    /// the field types its Default takes the defaults of
+    default_field_types: Vec<String>,
+    /// the traits it implements by hand rather than deriving, except Default
+    manual_impls: Vec<&'static str>,
+    /// the impl blocks that follow the declaration
+    impls_text: String,
+    /// its impl of Default, when that is written by hand
+    default_text: String,
+    /// for a constrained newtype, the scalar it holds, see get_constrained_newtype_type
+    constrained_scalar: Option<ConstrainedScalar>,
+    /// for a builder, the name of the struct it builds and the fields it sets, see apply_builders
+    builder_of: Option<(String, Vec<BuilderField>)>,
+}
+
+/// the scalar a constrained newtype holds: its Rust type, and the schema it must meet
+struct ConstrainedScalar {
+    type_name: String,
+    defn_map: Map<String, Value>,
+    draft: Draft,
+}
+
+/// a named field of a generated struct
+struct RustField {
+    field_name: String,
+    type_name: String,
+    /// an Option left out when None, see get_field_declaration
+    is_optional: bool,
+    /// whether a missing value deserializes to the Rust default, which its type may not have, see apply_defaults
+    has_default: bool,
+}
+
+impl RustType {
+    /// a type with the given derives, reaching serde by its configured path,
+    /// and telling the serde derives that path when it is not ::serde
+    fn new(kind: &'static str, name: &str, derives: &[&str], options: &GeneratorOptions) -> RustType {
+        let derive_paths: Vec<String> = derives.iter().map(|derive| match *derive {
+            "Serialize" | "Deserialize" => format!("{}::{}", options.serde_path(), derive),
+            _                           => derive.to_string(),
+        }).collect();
+        let mut attributes: Vec<String> = Vec::new();
+        if let Some(serde_crate) = &options.serde_crate {
+            if derives.contains(&"Serialize") || derives.contains(&"Deserialize") {
+                attributes.push(format!("#[serde(crate = {:?})]", serde_crate.trim_start_matches("::")));
+            }
+        }
+        RustType {
+            name: name.to_string(),
+            kind,
+            doc_text: "".to_string(),
+            derives: derive_paths,
+            attributes,
+            body_text: "".to_string(),
+            fields: None,
+            field_types: Vec::new(),
+            default_field_types: Vec::new(),
+            manual_impls: Vec::new(),
+            impls_text: "".to_string(),
+            default_text: "".to_string(),
+            constrained_scalar: None,
+            builder_of: None,
+        }
+    }
+
+    /// whether it has the trait, derived or implemented by hand
+    fn has_trait(&self, trait_name: &str) -> bool {
+        self.derives.iter().any(|derive| get_derive_name(derive) == trait_name) || self.manual_impls.contains(&trait_name)
+            || (trait_name == "Default" && !self.default_text.is_empty())
+    }
+
+    /// the Rust code for the type, declared with the visibility, e.g. "pub(crate)", or "" for private
+    fn to_text(&self, visibility: &str) -> String {
+        let attributes_text: String = self.attributes.iter().map(|attribute| format!("{}\r\n", attribute)).collect();
+        let visibility_text: String = if visibility.is_empty() { "".to_string() } else { format!("{} ", visibility) };
+        let body_text: String = match &self.fields {
+            Some(fields) => format!(" {{\n{}}}\n", fields.iter().map(get_field_text).collect::<String>()),
+            None         => self.body_text.to_string(),
+        };
+        format!("{}#[derive({})]\r\n{}{}{} {}{}{}{}", self.doc_text, self.derives.join(", "), attributes_text, visibility_text, self.kind, self.name,
+            body_text, self.impls_text, self.default_text)
+    }
+}
+
+/// the identifiers in a Rust type, e.g. Option, Vec and f64 for Option<Vec<f64>>
+fn get_type_names(type_name: &str) -> impl Iterator<Item = &str> {
+    type_name.split(|c: char| !c.is_ascii_alphanumeric() && c != '_').filter(|name| !name.is_empty())
+}
+
+/// the generated types a field type takes the Default of, e.g. Count for Count or [Count; 2], but none for
+/// Option<Count> or Vec<Count>, whose defaults are empty
+fn get_default_type_names<'a>(type_name: &'a str, type_names: &HashSet<&str>) -> Vec<&'a str> {
+    let outer_name: &str = type_name.split('<').next().unwrap_or_default();
+    if matches!(outer_name.rsplit("::").next(), Some("Option" | "Vec" | "BTreeSet" | "HashSet" | "BTreeMap" | "HashMap" | "Map")) {
+        return Vec::new();
+    }
+    get_type_names(type_name).filter(|name| type_names.contains(name)).collect()
+}
+
+/// leave Default off the types that can't have it: constrained newtypes without a valid default, see
+/// get_constrained_newtype_type, and the types with a field of such a type, whose fields then have to be
+/// given when deserialized rather than taking the default
+fn apply_defaults(rust_types: &mut [RustType]) {
+    loop {
+        let no_default_names: HashSet<String> = rust_types.iter().filter(|rust_type| !rust_type.has_trait("Default")).map(|rust_type| rust_type.name.to_string()).collect();
+        let no_default_names: HashSet<&str> = no_default_names.iter().map(|name| name.as_str()).collect();
+        let mut changed: bool = false;
+        for rust_type in rust_types.iter_mut() {
+            if let Some(fields) = &mut rust_type.fields {
+                for field in fields.iter_mut().filter(|field| field.has_default) {
+                    field.has_default = get_default_type_names(&field.type_name, &no_default_names).is_empty();
+                }
+            }
+            let is_default_blocked: bool = rust_type.default_field_types.iter().any(|field_type| !get_default_type_names(field_type, &no_default_names).is_empty());
+            if is_default_blocked && rust_type.has_trait("Default") {
+                rust_type.derives.retain(|derive| derive != "Default");
+                rust_type.default_text = "".to_string();
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+}
+
+/// add the extra derives and attributes to the generated types, leaving out any derive a type can't have,
+/// because of a field type such as f64 for Eq, or a field of another generated type left without it
+fn apply_derives(rust_types: &mut [RustType], options: &GeneratorOptions) {
+    if options.extra_derives.is_empty() && options.extra_attributes.is_empty() && options.custom_derive_map.is_empty() && options.custom_attribute_map.is_empty() {
+        return;
+    }
+    let positions: HashMap<String, usize> = rust_types.iter().enumerate().map(|(position, rust_type)| (rust_type.name.to_string(), position)).collect();
+    let field_type_names: Vec<HashSet<&str>> = rust_types.iter()
+        .map(|rust_type| rust_type.field_types.iter().flat_map(|field_type| get_type_names(field_type)).collect())
+        .collect();
+    // the extra derives each type is asked for, less those a field type can't have
+    let mut extra_derives: Vec<Vec<String>> = Vec::new();
+    for (rust_type, type_names) in rust_types.iter().zip(&field_type_names) {
+        let mut type_derives: Vec<String> = Vec::new();
+        for derive in options.extra_derives.iter().chain(options.custom_derive_map.get(&rust_type.name).into_iter().flatten()) {
+            let derive: String = derive.trim().to_string();
+            let is_blocked: bool = DERIVE_BLOCKERS.iter().any(|(blocked_derive, blockers)| {
+                get_derive_name(&derive) == *blocked_derive && blockers.iter().any(|blocker| type_names.contains(blocker))
+            });
+            if !is_blocked && !rust_type.has_trait(get_derive_name(&derive)) && !type_derives.contains(&derive) {
+                type_derives.push(derive);
+            }
+        }
+        extra_derives.push(type_derives);
+    }
+    // a derive also needs every field of a generated type to have it, which can take a few passes through nested types
+    let has_derive = |extra_derives: &[Vec<String>], position: usize, derive: &str| -> bool {
+        rust_types[position].has_trait(get_derive_name(derive))
+            || extra_derives[position].iter().any(|type_derive| get_derive_name(type_derive) == get_derive_name(derive))
+    };
+    loop {
+        let mut changed: bool = false;
+        for (position, type_names) in field_type_names.iter().enumerate() {
+            let type_derives: Vec<String> = extra_derives[position].clone();
+            let kept_derives: Vec<String> = type_derives.iter().filter(|derive| {
+                type_names.iter()
+                    .filter_map(|type_name| positions.get(*type_name))
+                    .filter(|field_position| **field_position != position)
+                    .all(|field_position| has_derive(&extra_derives, *field_position, derive))
+            }).cloned().collect();
+            if kept_derives.len() != type_derives.len() {
+                extra_derives[position] = kept_derives;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    for (rust_type, type_derives) in rust_types.iter_mut().zip(extra_derives) {
+        rust_type.derives.extend(type_derives);
+        // serde attributes only mean something to the serde derives, which some types implement by hand instead
+        let is_serde_derived: bool = rust_type.derives.iter().any(|derive| get_derive_name(derive) == "Serialize" || get_derive_name(derive) == "Deserialize");
+        let attributes: Vec<String> = options.extra_attributes.iter().chain(options.custom_attribute_map.get(&rust_type.name).into_iter().flatten())
+            .filter(|attribute| is_serde_derived || !attribute.replace(' ', "").starts_with("#[serde("))
+            .cloned()
+            .collect();
+        rust_type.attributes.extend(attributes);
+    }
+}
+
+/// a Rust field declaration
+fn get_field_text(field: &RustField) -> String {
+    if field.is_optional {
+        return format!("    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n    pub {}: {},\n", field.field_name, field.type_name);
+    }
+    if !field.has_default {
+        return format!("    pub {}: {},\n", field.field_name, field.type_name);
+    }
+    format!("    #[serde(default)]\n    pub {}: {},\n", field.field_name, field.type_name)
+}
+
+/// the Rust name and type of a property, and whether it is an Option left out when None:
+/// readOnly and writeOnly properties, and conditional ones, see get_conditional_names, or those marked x-rust-optional
+fn get_field_declaration(key_name: &str, field_pointer: &str, defn_value: &Value, is_conditional: bool, draft: Draft, options: &GeneratorOptions) -> (String, String, bool) {
+    let mut field_name: String = key_name.to_string();
+    if let Some(custom_name) = get_custom_override(&options.custom_name_map, field_pointer, key_name) {
+        field_name = custom_name.to_string();
+    }
+    let rust_type_name: String;
+    if let Some(custom_type) = get_custom_override(&options.custom_type_map, field_pointer, key_name) {
+        rust_type_name = custom_type.to_string();
+    } else if let Value::Object(defn_m) = defn_value {
+        let defn_map: Map<String, Value> = defn_m.clone();
+        rust_type_name = get_field_type(key_name, defn_map, draft, options);
+    } else {
+        panic!("Could not parse JSON Schema, bad defintion for {}\n", key_name);
+    }   
+    let is_one_way: bool = ["readOnly", "writeOnly"].iter().any(|one_way_keyword| defn_value.get(one_way_keyword) == Some(&Value::Bool(true)));
+    // conditional properties are left out when absent, as whether they are there decides what else must be
+    if is_one_way || is_conditional {
+        // readOnly and writeOnly properties are left out in one direction
+        let option_type_name: String = if rust_type_name.starts_with("Option<") { rust_type_name } else { format!("Option<{}>", rust_type_name) };
+        return (field_name, option_type_name, true);
+    }
+    (field_name, rust_type_name, false)
+}
+
+/// a field of a struct, as its builder sets it
+struct BuilderField {
+    field_name: String,
+    type_name: String,
+    is_required: bool,
+    default_value: Option<Value>,
+}
+
+/// the names in the required keyword of an object schema
+fn get_required(schema_json_map: &Map<String, Value>) -> Vec<&str> {
+    match schema_json_map.get("required") {
+        Some(Value::Array(required_names)) => required_names.iter().filter_map(|required_name| required_name.as_str()).collect(),
+        _                                  => Vec::new(),
+    }
+}
+
+/// a builder type for a struct, written out once the other types are known, see apply_builders
+fn get_builder_type(title: &str, builder_fields: Vec<BuilderField>, options: &GeneratorOptions) -> RustType {
+    let mut builder_type: RustType = RustType::new("struct", &format!("{}Builder", title), &["Clone", "Default"], options);
+    builder_type.doc_text = format!("/// builds a {}, see {}::builder()\r\n", title, title);
+    builder_type.field_types = builder_fields.iter().map(|builder_field| builder_field.type_name.to_string()).collect();
+    builder_type.builder_of = Some((title.to_string(), builder_fields));
+    builder_type
+}
+
+/// write out the builders, with builder() on the structs they build: a field holding a constrained newtype is set
+/// with the value it holds, and build() makes the newtype with TryFrom, failing with its error; build() also fails
+/// with the name of a required field that was not set, or of one whose type has no default, and fills in the schema
+/// default, or else the Rust default, for the other fields
+fn apply_builders(rust_types: &mut [RustType], options: &GeneratorOptions) {
+    let no_default_names: HashSet<String> = rust_types.iter().filter(|rust_type| !rust_type.has_trait("Default")).map(|rust_type| rust_type.name.to_string()).collect();
+    let no_default_names: HashSet<&str> = no_default_names.iter().map(|name| name.as_str()).collect();
+    let constrained_scalars: HashMap<&str, &ConstrainedScalar> = rust_types.iter()
+        .filter_map(|rust_type| rust_type.constrained_scalar.as_ref().map(|constrained_scalar| (rust_type.name.as_str(), constrained_scalar)))
+        .collect();
+    let mut builder_texts: Vec<(String, String)> = Vec::new();
+    for rust_type in rust_types.iter().filter(|rust_type| rust_type.builder_of.is_some()) {
+        let (title, builder_fields) = rust_type.builder_of.as_ref().unwrap();
+        builder_texts.push(get_builder_texts(&rust_type.name, title, builder_fields, &constrained_scalars, &no_default_names, options));
+    }
+    let mut builder_texts = builder_texts.into_iter();
+    for rust_type in rust_types.iter_mut().filter(|rust_type| rust_type.builder_of.is_some()) {
+        let (body_text, impls_text) = builder_texts.next().unwrap();
+        rust_type.body_text = body_text;
+        rust_type.impls_text = impls_text;
+    }
+}
+
+/// the body and impls of a builder, see apply_builders
+fn get_builder_texts(builder_name: &str, title: &str, builder_fields: &[BuilderField], constrained_scalars: &HashMap<&str, &ConstrainedScalar>,
+    no_default_names: &HashSet<&str>, options: &GeneratorOptions) -> (String, String) {
+    let mut builder_fields_text: String = "".to_string();
+    let mut setters_text: String = "".to_string();
+    let mut build_text: String = "".to_string();
+    for builder_field in builder_fields {
+        let BuilderField { field_name, type_name, is_required, default_value } = builder_field;
+        let required_text: String = format!("self.{field_name}.ok_or_else(|| \"{field_name} is required\".to_string())?");
+        // a constrained newtype, or an Option of one, is set with the value it holds
+        let option_item_name: Option<&str> = type_name.strip_prefix("Option<").and_then(|type_name| type_name.strip_suffix('>'));
+        let newtype_name: &str = option_item_name.unwrap_or(type_name);
+        let (set_type_name, value_text): (String, String) = match constrained_scalars.get(newtype_name) {
+            Some(constrained_scalar) => {
+                let ConstrainedScalar { type_name: inner_type_name, defn_map, draft } = constrained_scalar;
+                let try_from_text: String = format!("<{} as ::std::convert::TryFrom<{}>>::try_from", newtype_name, inner_type_name);
+                if let Some(default_value) = default_value.as_ref().filter(|default_value| !default_value.is_null()) {
+                    if !is_valid_scalar(defn_map, default_value, inner_type_name, *draft) {
+                        panic!("Could not parse JSON Schema, the default {} of {} does not meet its constraints\n", default_value, field_name);
+                    }
+                }
+                let default_text: Option<String> = default_value.as_ref().map(|default_value| {
+                    format!("self.{}.unwrap_or_else(|| {}::from_str({:?}).unwrap())", field_name, options.serde_json_path(), default_value.to_string())
+                });
+                match option_item_name {
+                    Some(_) => {
+                        let set_text: String = match (is_required, default_text) {
+                            (true, _)                  => required_text,
+                            (false, Some(default_text)) => default_text,
+                            (false, None)              => format!("self.{}.flatten()", field_name),
+                        };
+                        (format!("Option<{}>", inner_type_name), format!("{}.map({}).transpose()?", set_text, try_from_text))
+                    },
+                    None    => {
+                        let value_text: String = match (is_required, default_text) {
+                            (true, _)                  => format!("{}({})?", try_from_text, required_text),
+                            (false, Some(default_text)) => format!("{}({})?", try_from_text, default_text),
+                            (false, None) if no_default_names.contains(newtype_name) => format!("{}({})?", try_from_text, required_text),
+                            (false, None)              => format!("self.{}.map({}).transpose()?.unwrap_or_default()", field_name, try_from_text),
+                        };
+                        (inner_type_name.to_string(), value_text)
+                    },
+                }
+            },
+            None                     => {
+                let value_text: String = match (is_required, default_value) {
+                    (true, _)               => required_text,
+                    (false, Some(default))  => {
+                        if !is_value_of_type(default, type_name) {
+                            panic!("Could not parse JSON Schema, the default {} of {} is not a {}\n", default, field_name, type_name);
+                        }
+                        // the schema default, which a generated type can only check here
+                        format!("self.{field_name}.map_or_else(|| {}::from_str({:?}).map_err(|error| format!(\"the default of {field_name} does not fit: {{}}\", error)), Ok)?",
+                            options.serde_json_path(), default.to_string())
+                    },
+                    (false, None) if !get_default_type_names(type_name, no_default_names).is_empty() => required_text,
+                    (false, None)           => format!("self.{field_name}.unwrap_or_default()"),
+                };
+                (type_name.to_string(), value_text)
+            },
+        };
+        builder_fields_text += &format!("    {}: Option<{}>,\n", field_name, set_type_name);
+        setters_text += &format!("    /// set {field_name}\n    pub fn {field_name}(mut self, value: impl Into<{set_type_name}>) -> Self {{\n        self.{field_name} = Some(value.into());\n        self\n    }}\n\n");
+        build_text += &format!("            {}: {},\n", field_name, value_text);
+    }
+    let impls_text: String = format!(r#"
+impl {builder_name} {{
+{setters_text}    /// the {title}, or an error naming a required field that was not set, or a value that does not meet its constraints
+    pub fn build(self) -> Result<{title}, String> {{
+        Ok({title} {{
+{build_text}        }})
+    }}
+}}
+
+impl {title} {{
+    /// a builder, for setting the fields one at a time
+    pub fn builder() -> {builder_name} {{
+        {builder_name}::default()
+    }}
+}}
+"#);
+    (format!(" {{\n{}}}\n", builder_fields_text), impls_text)
+}
+
+/// the custom name or type for a property, given by the JSON Pointer to the property (e.g. "/properties/info/properties/seed"),
+/// a dotted path (e.g. "info.seed", "results[].name", "$defs.veggie.veggieName"), either with * and ** globs, or else the bare key
+fn get_custom_override<'a>(custom_map: &'a HashMap<String, String>, field_pointer: &str, key_name: &str) -> Option<&'a String> {
+    if let Some(custom_value) = custom_map.get(field_pointer) {
+        return Some(custom_value);
+    }
+    // the most specific (longest) pattern wins, then the first in order, so the choice does not depend on the map
+    let pattern_match: Option<(&String, &String)> = custom_map.iter()
+        .filter(|(pattern, _)| pattern.starts_with('/') || pattern.contains('.') || pattern.contains('*'))
+        .filter(|(pattern, _)| Regex::new(&get_pointer_pattern_regex(pattern)).map(|regex| regex.is_match(field_pointer)).unwrap_or(false))
+        .max_by(|(pattern_a, _), (pattern_b, _)| pattern_a.len().cmp(&pattern_b.len()).then_with(|| pattern_b.cmp(pattern_a)));
+    if let Some((_, custom_value)) = pattern_match {
+        return Some(custom_value);
+    }
+    custom_map.get(key_name)
+}
+
+/// a regex matching the JSON Pointers a pointer or dotted path pattern refers to, * being one key and ** any number
+fn get_pointer_pattern_regex(pattern: &str) -> String {
+    let pointer_pattern: String = if pattern.starts_with('/') {
+        pattern.to_string()
+    } else {
+        let mut pointer_pattern: String = String::new();
+        let mut is_def_name: bool = false;
+        for segment in pattern.split('.') {
+            let (key, item_count) = (segment.trim_end_matches("[]"), (segment.len() - segment.trim_end_matches("[]").len()) / 2);
+            if is_def_name {
+                pointer_pattern = format!("{}/{}", pointer_pattern, escape_pointer_token(key));
+                is_def_name = false;
+            } else if key == "$defs" || key == "definitions" {
+                pointer_pattern = format!("{}/{}", pointer_pattern, key);
+                is_def_name = true;
+            } else if key == "**" {
+                pointer_pattern = format!("{}/**", pointer_pattern);
+            } else {
+                pointer_pattern = format!("{}/properties/{}", pointer_pattern, escape_pointer_token(key));
+            }
+            pointer_pattern += &"/items".repeat(item_count);
+        }
+        pointer_pattern
+    };
+    let regex_text: String = pointer_pattern.split("**")
+        .map(|part| part.split('*').map(regex::escape).collect::<Vec<String>>().join("[^/]*"))
+        .collect::<Vec<String>>()
+        .join(".*");
+    format!("^{}$", regex_text)
+}
+
+/// get the rust field type from definition JSON object
+fn get_field_type(key_name: &str, defn_map: Map<String, Value>, draft: Draft, options: &GeneratorOptions) -> String {
+    if let Some(Value::String(rust_type_name)) = defn_map.get("x-rust-type") {
+        // serde_json is reached by its configured path, as in the rest of the generated code
+        return match rust_type_name.trim_start_matches("::").strip_prefix("serde_json::") {
+            Some(serde_json_item) => format!("{}::{}", options.serde_json_path(), serde_json_item),
+            None                  => rust_type_name.to_string(),
+        };
+    }
+    if let Some((_, _, inner_type)) = get_nullable(&defn_map) {
+        return format!("Option<{}>", get_field_type(key_name, inner_type.clone(), draft, options));
+    }
+    if let Some(ref_value) = defn_map.get("$ref") {
+        if let Value::String(ref_name) = ref_value {
+            let defs_prefix = format!("#/{}/", draft.defs_keyword());
+            if let Some(def_name) = ref_name.strip_prefix(&defs_prefix) {
+                let referenced_type = format_struct_name(def_name, &options.custom_name_map);
+                return referenced_type;
+            } else {
+                panic!("Could not parse JSON Schema, unknown $ref for {}\n", key_name);
+            }
+        } else {
+            panic!("Could not parse JSON Schema, invalid $ref for {}\n", key_name);
+        }
+    }
+    if !defn_map.contains_key("type") {
+        // anything goes
+        return format!("{}::Value", options.serde_json_path());
+    }
+    let json_type_name = defn_map["type"].as_str().unwrap();
+    match json_type_name {
+        "array"      => {
+                            if let Some((prefix_items, tail_items)) = get_positional_items(&defn_map, draft) {
+                                if tail_items != Value::Bool(false) {
+                                    panic!("Could not parse JSON Schema, positional array with more items for {} must be a $def\n", key_name);
+                                }
+                                let item_type_names: Vec<String> = prefix_items.iter().enumerate()
+                                    .map(|(position, prefix_item)| get_item_type(&format!("{}[{}]", key_name, position), prefix_item, draft, options))
+                                    .collect();
+                                if item_type_names.len() == 1 {
+                                    return format!("({},)", item_type_names[0]);
+                                }
+                                return format!("({})", item_type_names.join(", "));
+                            }
+                            get_collection_type(key_name, &defn_map, draft, options)
+                        },
+        "object"     if get_map_values_schema(&defn_map).is_some() => {
+                            let values_type_map: Map<String, Value> = get_map_values_schema(&defn_map).unwrap().clone();
+                            options.typed_map_type(&get_field_type(&format!("{}{{}}", key_name), values_type_map, draft, options))
+                        },
+        // objects with properties are $defs by now
+        "object"     => options.free_form_object_type(),
+        _            => {
+                            match defn_map.get("format").and_then(|format_name| format_name.as_str()).and_then(|format_name| get_format_rust_type(json_type_name, format_name)) {
+                                Some(rust_type_name) => rust_type_name.to_string(),
+                                None                 => get_simple_rust_type(json_type_name),
+                            }
+                        }     
+    }
+}
+
+
+/// the schemas of the leading items of a positional array, and the schema for any items after them
+/// (true when unrestricted, false when there can be no more)
+fn get_positional_items(defn_map: &Map<String, Value>, draft: Draft) -> Option<(Vec<Value>, Value)> {
+    let (prefix_items, tail_items) = if draft.has_prefix_items() {
+        (defn_map.get("prefixItems"), defn_map.get("items"))
+    } else {
+        (defn_map.get("items"), defn_map.get("additionalItems"))
+    };
+    let prefix_items: Vec<Value> = match prefix_items {
+        Some(Value::Array(prefix_items)) => prefix_items.clone(),
+        _                                => return None,
+    };
+    let max_items: Option<u64> = defn_map.get("maxItems").and_then(|max_items| max_items.as_u64());
+    let tail_items: Value = match tail_items {
+        Some(tail_items)                                                   => tail_items.clone(),
+        None if max_items.map(|m| m <= prefix_items.len() as u64) == Some(true) => Value::Bool(false),
+        None                                                               => Value::Bool(true),
+    };
+    Some((prefix_items, tail_items))
+}
+
+/// the Rust type of an array item, which may be a boolean schema
+fn get_item_type(key_name: &str, item_value: &Value, draft: Draft, options: &GeneratorOptions) -> String {
+    match item_value {
+        Value::Object(item_type_m) => get_field_type(key_name, item_type_m.clone(), draft, options),
+        Value::Bool(true)          => format!("{}::Value", options.serde_json_path()),
+        _                          => panic!("Could not parse JSON Schema, invalid array item type for {}\n", key_name),
+    }
+}
+
+/// convert a positional array to a Rust tuple struct, with a trailing Vec for any further items
+fn get_tuple_struct_type(title: &str, prefix_items: &[Value], tail_items: &Value, draft: Draft, options: &GeneratorOptions) -> RustType {
+    let mut item_type_names: Vec<String> = prefix_items.iter().enumerate()
+        .map(|(position, prefix_item)| get_item_type(&format!("{}[{}]", title, position), prefix_item, draft, options))
+        .collect();
+    if *tail_items == Value::Bool(false) {
+        let mut tuple_type: RustType = RustType::new("struct", title, &["Clone", "Serialize", "Deserialize", "Default"], options);
+        tuple_type.body_text = format!("({});\n", item_type_names.iter().map(|item_type_name| format!("pub {}", item_type_name)).collect::<Vec<String>>().join(", "));
+        tuple_type.field_types = item_type_names.clone();
+        tuple_type.default_field_types = item_type_names;
+        return tuple_type;
+    }
+    let tail_type_name: String = get_item_type(&format!("{}[]", title), tail_items, draft, options);
+    item_type_names.push(format!("Vec<{}>", tail_type_name));
+    let mut tuple_type: RustType = RustType::new("struct", title, &["Clone", "Default"], options);
+    tuple_type.body_text = format!("({});\n", item_type_names.iter().map(|item_type_name| format!("pub {}", item_type_name)).collect::<Vec<String>>().join(", "));
+    tuple_type.field_types = item_type_names.clone();
+    tuple_type.default_field_types = item_type_names;
+    tuple_type.manual_impls = vec!["Serialize", "Deserialize"];
+    let tail_position: usize = prefix_items.len();
+    let mut serialize_elements: String = "".to_string();
+    let mut deserialize_elements: String = "".to_string();
+    let mut element_names: Vec<String> = Vec::new();
+    for position in 0..tail_position {
+        serialize_elements += &format!("        seq.serialize_element(&self.{})?;\n", position);
+        deserialize_elements += &format!("                let item_{} = seq.next_element()?.ok_or_else(|| {}::de::Error::invalid_length({}, &self))?;\n", position, options.serde_path(), position);
+        element_names.push(format!("item_{}", position));
+    }
+    element_names.push("rest".to_string());
+    tuple_type.impls_text = format!(r#"
+impl {serde}::Serialize for {title} {{
+    fn serialize<S: {serde}::Serializer>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error> {{
+        use {serde}::ser::SerializeSeq;
+        let mut seq = serializer.serialize_seq(Some({tail_position} + self.{tail_position}.len()))?;
+{serialize_elements}        for item in &self.{tail_position} {{
+            seq.serialize_element(item)?;
+        }}
+        seq.end()
+    }}
+}}
+
+impl<'de> {serde}::Deserialize<'de> for {title} {{
+    fn deserialize<D: {serde}::Deserializer<'de>>(deserializer: D) -> ::std::result::Result<Self, D::Error> {{
+        struct TupleVisitor;
+        impl<'de> {serde}::de::Visitor<'de> for TupleVisitor {{
+            type Value = {title};
+            fn expecting(&self, formatter: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {{
+                formatter.write_str("an array of at least {tail_position} items")
+            }}
+            fn visit_seq<A: {serde}::de::SeqAccess<'de>>(self, mut seq: A) -> ::std::result::Result<Self::Value, A::Error> {{
+{deserialize_elements}                let mut rest = Vec::new();
+                while let Some(item) = seq.next_element()? {{
+                    rest.push(item);
+                }}
+                Ok({title}({elements}))
+            }}
+        }}
+        deserializer.deserialize_seq(TupleVisitor)
+    }}
+}}
+"#, title = title, serde = options.serde_path(), tail_position = tail_position,
+        serialize_elements = serialize_elements, deserialize_elements = deserialize_elements, elements = element_names.join(", "));
+    tuple_type
+}
+
+/// the collection for an array: its x-rust-collection, else the choice for its field, else the global choice for its bounds
+fn get_collection(key_name: &str, defn_map: &Map<String, Value>, options: &GeneratorOptions) -> Collection {
+    if let Some(Value::String(collection_name)) = defn_map.get("x-rust-collection") {
+        return match Collection::from_name(collection_name) {
+            Some(collection) => collection,
+            None             => panic!("Could not parse JSON Schema, unknown x-rust-collection {} for {}\n", collection_name, key_name),
+        };
+    }
+    if let Some(collection) = options.custom_collection_map.get(key_name) {
+        return *collection;
+    }
+    let (min_items, max_items) = get_array_bounds(defn_map);
+    if defn_map.get("uniqueItems") == Some(&Value::Bool(true)) && options.unique_items != Collection::Vec {
+        options.unique_items
+    } else if min_items.is_some() && min_items == max_items && options.fixed_items != Collection::Vec {
+        options.fixed_items
+    } else if min_items.unwrap_or(0) >= 1 && options.min_items != Collection::Vec {
+        options.min_items
+    } else {
+        Collection::Vec
+    }
+}
+
+/// the minItems and maxItems of an array, if given
+fn get_array_bounds(defn_map: &Map<String, Value>) -> (Option<u64>, Option<u64>) {
+    (defn_map.get("minItems").and_then(|min_items| min_items.as_u64()), defn_map.get("maxItems").and_then(|max_items| max_items.as_u64()))
+}
+
+/// the Rust collection type of a non-positional array, falling back to Vec where the global choice does not fit
+fn get_collection_type(key_name: &str, defn_map: &Map<String, Value>, draft: Draft, options: &GeneratorOptions) -> String {
+    let item_type_name: String = if let Some(item_type_m) = get_array_items_schema(defn_map, draft) {
+        get_field_type(&format!("{}[]", key_name), item_type_m.clone(), draft, options)
+    } else {
+        panic!("Could not parse JSON Schema, no array item type for {}\n", key_name);
+    };
+    let collection: Collection = get_collection(key_name, defn_map, options);
+    let is_chosen: bool = defn_map.contains_key("x-rust-collection") || options.custom_collection_map.contains_key(key_name);
+    let (min_items, max_items) = get_array_bounds(defn_map);
+    match collection {
+        Collection::BTreeSet | Collection::HashSet if !is_hashable_type(&item_type_name) => {
+            if is_chosen {
+                panic!("Could not parse JSON Schema, {} items of {} are not hashable\n", item_type_name, key_name);
+            }
+            format!("Vec<{}>", item_type_name)
+        },
+        Collection::BTreeSet => format!("::std::collections::BTreeSet<{}>", item_type_name),
+        Collection::HashSet  => format!("::std::collections::HashSet<{}>", item_type_name),
+        Collection::Array    => match (min_items, max_items) {
+            // serde and Default are implemented for arrays up to 32 items
+            (Some(min_items), Some(max_items)) if min_items == max_items && max_items <= 32 => format!("[{}; {}]", item_type_name, max_items),
+            _ if is_chosen => panic!("Could not parse JSON Schema, {} needs minItems == maxItems <= 32 for a fixed-size array\n", key_name),
+            _              => format!("Vec<{}>", item_type_name),
+        },
+        Collection::NonEmpty => panic!("Could not parse JSON Schema, non-empty array {} must be a $def\n", key_name),
+        Collection::Vec      => format!("Vec<{}>", item_type_name),
+    }
+}
+
+/// whether a Rust type can be kept in a BTreeSet or HashSet
+fn is_hashable_type(type_name: &str) -> bool {
+    if let Some(tuple_types) = type_name.strip_prefix('(').and_then(|tuple_types| tuple_types.strip_suffix(')')) {
+        return tuple_types.split(',').map(|tuple_type| tuple_type.trim()).filter(|tuple_type| !tuple_type.is_empty()).all(is_hashable_type);
+    }
+    matches!(type_name, "bool" | "String" | "i32" | "i64" | "u32" | "u64" | "char")
+}
+
+/// convert a non-positional array to a Rust newtype, or to a wrapper that holds at least minItems items
+fn get_array_struct_type(title: &str, defn_map: &Map<String, Value>, draft: Draft, options: &GeneratorOptions) -> RustType {
+    if get_collection("", defn_map, options) != Collection::NonEmpty {
+        return get_newtype_struct_type(title, &get_collection_type("", defn_map, draft, options), options);
+    }
+    let item_type_name: String = if let Some(item_type_m) = get_array_items_schema(defn_map, draft) {
+        get_field_type(&format!("{}[]", title), item_type_m.clone(), draft, options)
+    } else {
+        panic!("Could not parse JSON Schema, no array item type for {}\n", title);
+    };
+    let min_items: u64 = get_array_bounds(defn_map).0.unwrap_or(1).max(1);
+    let too_short: String = if min_items == 1 { "items.is_empty()".to_string() } else { format!("items.len() < {}", min_items) };
+    let mut array_type: RustType = RustType::new("struct", title, &["Clone"], options);
+    array_type.body_text = format!("(Vec<{}>);\n", item_type_name);
+    array_type.field_types = vec![format!("Vec<{}>", item_type_name)];
+    array_type.default_field_types = vec![item_type_name.to_string()];
+    array_type.manual_impls = vec!["Deref", "Serialize", "Deserialize"];
+    array_type.impls_text = format!(r#"
+impl {title} {{
+    /// the items, or the items back if there are fewer than {min_items}
+    pub fn new(items: Vec<{item}>) -> ::std::result::Result<Self, Vec<{item}>> {{
+        if {too_short} {{
+            return Err(items);
+        }}
+        Ok({title}(items))
+    }}
+
+    pub fn first(&self) -> &{item} {{
+        &self.0[0]
+    }}
+
+    pub fn into_vec(self) -> Vec<{item}> {{
+        self.0
+    }}
+}}
+
+impl ::std::ops::Deref for {title} {{
+    type Target = [{item}];
+    fn deref(&self) -> &[{item}] {{
+        &self.0
+    }}
+}}
+
+impl {serde}::Serialize for {title} {{
+    fn serialize<S: {serde}::Serializer>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error> {{
+        {serde}::Serialize::serialize(&self.0, serializer)
+    }}
+}}
+
+impl<'de> {serde}::Deserialize<'de> for {title} {{
+    fn deserialize<D: {serde}::Deserializer<'de>>(deserializer: D) -> ::std::result::Result<Self, D::Error> {{
+        let items: Vec<{item}> = {serde}::Deserialize::deserialize(deserializer)?;
+        {title}::new(items).map_err(|items| {serde}::de::Error::invalid_length(items.len(), &"at least {min_items} items"))
+    }}
+}}
+"#, title = title, serde = options.serde_path(), item = item_type_name, min_items = min_items, too_short = too_short);
+    array_type.default_text = format!(r#"
+impl Default for {title} {{
+    fn default() -> Self {{
+        {title}(vec![Default::default(); {min_items}])
+    }}
+}}
+"#, title = title, min_items = min_items);
+    array_type
+}
+
+/// a doc comment saying a string's pattern is not checked, when the path to the regex crate is not given
+fn get_unchecked_pattern_doc(defn_map: &Map<String, Value>, type_name: &str, options: &GeneratorOptions) -> String {
+    match defn_map.get("pattern").and_then(|pattern| pattern.as_str()) {
+        Some(pattern) if type_name == "String" && options.regex_crate.is_none() => {
+            format!("/// the pattern {} is not checked, as no path to the regex crate was given with $regex\r\n", pattern)
+        },
+        _ => "".to_string(),
+    }
+}
+
+/// the checks of a scalar schema's length, range and pattern constraints, as (condition a bad value meets, error message);
+/// a pattern is only checked when the path to the regex crate is given, as the generated code can't rely on it otherwise,
+/// see get_unchecked_pattern_doc
+fn get_scalar_checks(defn_map: &Map<String, Value>, type_name: &str, draft: Draft, options: &GeneratorOptions) -> Vec<(String, String)> {
+    let mut checks: Vec<(String, String)> = Vec::new();
+    if type_name == "String" {
+        // JSON Schema lengths count characters, not bytes
+        if let Some(min_length) = defn_map.get("minLength").and_then(|min_length| min_length.as_u64()) {
+            checks.push((format!("value.chars().count() < {}", min_length), format!("must be at least {} characters long", min_length)));
+        }
+        if let Some(max_length) = defn_map.get("maxLength").and_then(|max_length| max_length.as_u64()) {
+            checks.push((format!("value.chars().count() > {}", max_length), format!("must be at most {} characters long", max_length)));
+        }
+        if let (Some(pattern), Some(regex_crate)) = (defn_map.get("pattern").and_then(|pattern| pattern.as_str()), &options.regex_crate) {
+            if Regex::new(pattern).is_err() {
+                panic!("Could not parse JSON Schema, invalid pattern {}\n", pattern);
+            }
+            // the regex is compiled once, on first use
+            checks.push((format!("!{{ static PATTERN: ::std::sync::OnceLock<{0}::Regex> = ::std::sync::OnceLock::new(); PATTERN.get_or_init(|| {0}::Regex::new({1:?}).unwrap()) }}.is_match(&value)", regex_crate, pattern),
+                format!("must match {}", pattern)));
+        }
+    } else if matches!(type_name, "i32" | "i64" | "u32" | "u64" | "f32" | "f64") {
+        // compared as f64, as the bounds need not be whole numbers
+        let get_bound = |keyword: &str| defn_map.get(keyword).and_then(|bound| bound.as_f64());
+        let is_exclusive = |keyword: &str| draft.has_boolean_exclusive_bounds() && defn_map.get(keyword) == Some(&Value::Bool(true));
+        if let Some(minimum) = get_bound("minimum") {
+            if is_exclusive("exclusiveMinimum") {
+                checks.push((format!("(value as f64) <= {:?}", minimum), format!("must be greater than {}", minimum)));
+            } else {
+                checks.push((format!("(value as f64) < {:?}", minimum), format!("must be at least {}", minimum)));
+            }
+        }
+        if let Some(maximum) = get_bound("maximum") {
+            if is_exclusive("exclusiveMaximum") {
+                checks.push((format!("(value as f64) >= {:?}", maximum), format!("must be less than {}", maximum)));
+            } else {
+                checks.push((format!("(value as f64) > {:?}", maximum), format!("must be at most {}", maximum)));
+            }
+        }
+        if !draft.has_boolean_exclusive_bounds() {
+            if let Some(exclusive_minimum) = get_bound("exclusiveMinimum") {
+                checks.push((format!("(value as f64) <= {:?}", exclusive_minimum), format!("must be greater than {}", exclusive_minimum)));
+            }
+            if let Some(exclusive_maximum) = get_bound("exclusiveMaximum") {
+                checks.push((format!("(value as f64) >= {:?}", exclusive_maximum), format!("must be less than {}", exclusive_maximum)));
+            }
+        }
+        if let Some(multiple_of) = get_bound("multipleOf").filter(|multiple_of| *multiple_of > 0.0) {
+            // allowing for rounding, e.g. 19.99 / 0.01 is not quite 1999
+            checks.push((format!("((value as f64) / {0:?} - ((value as f64) / {0:?}).round()).abs() > 1e-9", multiple_of), format!("must be a multiple of {}", multiple_of)));
+        }
+    }
+    checks
+}
+
+/// a Rust newtype for a constrained scalar, that can only hold values meeting the constraints:
+/// made with TryFrom, and checked again when deserialized; it has Default only when there is a default that meets
+/// the constraints, the schema's, which must, or else Rust's
+fn get_constrained_newtype_type(title: &str, type_name: &str, checks: &[(String, String)], defn_map: &Map<String, Value>, draft: Draft, options: &GeneratorOptions) -> RustType {
+    let checks_text: String = checks.iter()
+        .map(|(condition, message)| format!("        if {} {{\n            return Err(format!(\"{{}} {{}}\", value, {:?}));\n        }}\n", condition, message))
+        .collect();
+    let mut newtype: RustType = RustType::new("struct", title, &["Clone"], options);
+    newtype.body_text = format!("({});\n", type_name);
+    newtype.field_types = vec![type_name.to_string()];
+    newtype.manual_impls = vec!["TryFrom", "Deref", "Display", "Serialize", "Deserialize"];
+    newtype.constrained_scalar = Some(ConstrainedScalar { type_name: type_name.to_string(), defn_map: defn_map.clone(), draft });
+    match defn_map.get("default") {
+        Some(default_value) => {
+            if !is_valid_scalar(defn_map, default_value, type_name, draft) {
+                panic!("Could not parse JSON Schema, the default {} of {} does not meet its constraints\n", default_value, title);
+            }
+            newtype.default_text = format!(r#"
+impl Default for {title} {{
+    fn default() -> Self {{
+        {title}({serde_json}::from_str({literal:?}).unwrap())
+    }}
+}}
+"#, title = title, serde_json = options.serde_json_path(), literal = default_value.to_string());
+        },
+        None                => {
+            if get_zero_value(type_name).is_some_and(|zero_value| is_valid_scalar(defn_map, &zero_value, type_name, draft)) {
+                newtype.derives.push("Default".to_string());
+            }
+        },
+    }
+    newtype.impls_text = format!(r#"
+impl {title} {{
+    pub fn into_inner(self) -> {inner} {{
+        self.0
+    }}
+}}
+
+impl ::std::convert::TryFrom<{inner}> for {title} {{
+    type Error = String;
+    fn try_from(value: {inner}) -> ::std::result::Result<Self, String> {{
+{checks}        Ok({title}(value))
+    }}
+}}
+
+impl ::std::ops::Deref for {title} {{
+    type Target = {inner};
+    fn deref(&self) -> &{inner} {{
+        &self.0
+    }}
+}}
+
+impl ::std::fmt::Display for {title} {{
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {{
+        ::std::fmt::Display::fmt(&self.0, f)
+    }}
+}}
+
+impl {serde}::Serialize for {title} {{
+    fn serialize<S: {serde}::Serializer>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error> {{
+        {serde}::Serialize::serialize(&self.0, serializer)
+    }}
+}}
+
+impl<'de> {serde}::Deserialize<'de> for {title} {{
+    fn deserialize<D: {serde}::Deserializer<'de>>(deserializer: D) -> ::std::result::Result<Self, D::Error> {{
+        let value: {inner} = {serde}::Deserialize::deserialize(deserializer)?;
+        <{title} as ::std::convert::TryFrom<{inner}>>::try_from(value).map_err({serde}::de::Error::custom)
+    }}
+}}
+"#, title = title, inner = type_name, checks = checks_text, serde = options.serde_path());
+    newtype
+}
+
+/// whether a value meets the constraints of a scalar schema, as checked when generating, and can be held by its Rust type
+fn is_valid_scalar(defn_map: &Map<String, Value>, value: &Value, type_name: &str, draft: Draft) -> bool {
+    is_value_of_type(value, type_name) && validate_with_remotes(&Value::Object(defn_map.clone()), value, &HashMap::new(), Some(draft)).is_ok()
+}
+
+/// the Rust default of a scalar type, as JSON, e.g. 0 for i32
+fn get_zero_value(type_name: &str) -> Option<Value> {
+    match type_name {
+        "String"                                                        => Some(Value::String("".to_string())),
+        "bool"                                                          => Some(Value::Bool(false)),
+        "i8" | "i16" | "i32" | "i64" | "u8" | "u16" | "u32" | "u64"     => Some(serde_json::json!(0)),
+        "f32" | "f64"                                                   => Some(serde_json::json!(0.0)),
+        _                                                               => None,
+    }
+}
+
+/// whether a JSON value deserializes to a Rust type the generator gives fields, e.g. not "oops" to an i32 or 300
+/// to a u8, taking other types, such as generated ones, to fit
+fn is_value_of_type(value: &Value, type_name: &str) -> bool {
+    let fits = |fit: std::result::Result<(), serde_json::Error>| fit.is_ok();
+    match type_name.trim() {
+        "String" => fits(serde_json::from_value::<String>(value.clone()).map(|_| ())),
+        "bool"   => value.is_boolean(),
+        "i8"     => fits(serde_json::from_value::<i8>(value.clone()).map(|_| ())),
+        "i16"    => fits(serde_json::from_value::<i16>(value.clone()).map(|_| ())),
+        "i32"    => fits(serde_json::from_value::<i32>(value.clone()).map(|_| ())),
+        "i64"    => fits(serde_json::from_value::<i64>(value.clone()).map(|_| ())),
+        "u8"     => fits(serde_json::from_value::<u8>(value.clone()).map(|_| ())),
+        "u16"    => fits(serde_json::from_value::<u16>(value.clone()).map(|_| ())),
+        "u32"    => fits(serde_json::from_value::<u32>(value.clone()).map(|_| ())),
+        "u64"    => fits(serde_json::from_value::<u64>(value.clone()).map(|_| ())),
+        "f32" | "f64" => value.is_number(),
+        type_name => match type_name.strip_suffix('>').and_then(|type_name| type_name.split_once('<')) {
+            Some((generic_name, item_type_name)) => match generic_name.rsplit("::").next() {
+                Some("Option")                         => value.is_null() || is_value_of_type(value, item_type_name),
+                Some("Vec" | "BTreeSet" | "HashSet") => value.as_array().is_some_and(|items| items.iter().all(|item| is_value_of_type(item, item_type_name))),
+                _                                      => true,
+            },
+            None => true,
+        },
+    }
+}
+
+/// the schema every item of an array must match, if the array is not positional
+fn get_array_items_schema(defn_map: &Map<String, Value>, draft: Draft) -> Option<&Map<String, Value>> {
+    if draft.has_prefix_items() && defn_map.contains_key("prefixItems") {
+        return None;
+    }
+    match defn_map.get("items") {
+        Some(Value::Object(items_map)) => Some(items_map),
+        _                              => None,
+    }
+}
+
+/// whether get_field_type can give a Rust type for a schema that has no properties
+fn has_field_type(defn_map: &Map<String, Value>) -> bool {
+    if defn_map.contains_key("$ref") || defn_map.contains_key("x-rust-type") || get_nullable(defn_map).is_some() || get_map_values_schema(defn_map).is_some() || is_free_form_object(defn_map) {
+        return true;
+    }
+    match defn_map.get("type") {
+        Some(Value::String(json_type_name)) => json_type_name != "object",
+        Some(_)                             => false,
+        None                                => true,
+    }
+}
+
+/// whether a schema is an object without properties, whose properties can be anything
+fn is_free_form_object(defn_map: &Map<String, Value>) -> bool {
+    if defn_map.get("type") != Some(&Value::String("object".to_string())) || defn_map.contains_key("properties") {
+        return false;
+    }
+    match defn_map.get("additionalProperties") {
+        None | Some(Value::Bool(true))  => true,
+        Some(Value::Object(values_map)) => values_map.is_empty(),
+        _                               => false,
+    }
+}
+
+/// the schema of the values of an object that is only a map, i.e. has additionalProperties but no properties
+fn get_map_values_schema(defn_map: &Map<String, Value>) -> Option<&Map<String, Value>> {
+    if defn_map.get("type") != Some(&Value::String("object".to_string())) || defn_map.contains_key("properties") {
+        return None;
+    }
+    match defn_map.get("additionalProperties") {
+        // {} allows anything, which is a free-form object
+        Some(Value::Object(values_map)) if !values_map.is_empty() => Some(values_map),
+        _                                                         => None,
+    }
+}
+
+/// the union keyword, position and schema of the non-null branch of a two branch anyOf/oneOf with a null branch
+pub(crate) fn get_nullable(defn_map: &Map<String, Value>) -> Option<(&'static str, usize, &Map<String, Value>)> {
+    for union_keyword in ["anyOf", "oneOf"] {
+        if let Some(Value::Array(branches)) = defn_map.get(union_keyword) {
+            if branches.len() != 2 {
+                continue;
+            }
+            let is_null = |branch: &Value| branch.get("type") == Some(&Value::String("null".to_string()));
+            for (inner_position, inner_branch) in branches.iter().enumerate() {
+                if let (Value::Object(inner_type), true) = (inner_branch, is_null(&branches[1 - inner_position])) {
+                    return Some((union_keyword, inner_position, inner_type));
+                }
+            }
+        }
+    }
+    None
+}
+
+/// the values of a string enum, e.g. { "type": "string", "enum": ["a", "b"] }
+pub(crate) fn get_string_enum(defn_map: &Map<String, Value>) -> Option<Vec<String>> {
+    if defn_map.get("type").map(|json_type| json_type != "string") == Some(true) {
+        return None;
+    }
+    match defn_map.get("enum") {
+        Some(Value::Array(enum_values)) if !enum_values.is_empty() => enum_values.iter().map(|enum_value| enum_value.as_str().map(|enum_str| enum_str.to_string())).collect(),
+        _                                                          => None,
+    }
+}
+
+/// the branches of a tagged union, each with its tag value
+type TaggedBranches = Vec<(String, Map<String, Value>)>;
+
+/// the tag property and each branch with its tag value, for a oneOf/anyOf whose branches are objects told apart by a constant property
+pub(crate) fn get_discriminator(defn_map: &Map<String, Value>) -> Option<(String, TaggedBranches)> {
+    let branches: &Vec<Value> = match (defn_map.get("oneOf"), defn_map.get("anyOf")) {
+        (Some(Value::Array(branches)), _) | (None, Some(Value::Array(branches))) if !branches.is_empty() => branches,
+        _                                                                                                => return None,
+    };
+    let get_tag_value = |branch: &Value, tag_name: &str| -> Option<String> {
+        let tag_type = branch.get("properties")?.get(tag_name)?;
+        match (tag_type.get("const"), tag_type.get("enum")) {
+            (Some(Value::String(tag_value)), _)                               => Some(tag_value.to_string()),
+            (None, Some(Value::Array(tag_values))) if tag_values.len() == 1 => tag_values[0].as_str().map(|tag_value| tag_value.to_string()),
+            _                                                                 => None,
+        }
+    };
+    let tag_names: Vec<String> = match branches[0].get("properties") {
+        Some(Value::Object(props_map)) => props_map.keys().cloned().collect(),
+        _                              => return None,
+    };
+    for tag_name in tag_names {
+        let tagged_branches: Option<TaggedBranches> = branches.iter()
+            .map(|branch| Some((get_tag_value(branch, &tag_name)?, branch.as_object()?.clone())))
+            .collect();
+        if let Some(tagged_branches) = tagged_branches {
+            let mut tag_values: Vec<&String> = tagged_branches.iter().map(|(tag_value, _)| tag_value).collect();
+            tag_values.sort();
+            tag_values.dedup();
+            if tag_values.len() == tagged_branches.len() {
+                return Some((tag_name, tagged_branches));
+            }
+        }
+    }
+    None
+}
+
+/// convert a string enum to a Rust enum, defaulting to its first value
+fn get_string_enum_type(title: &str, enum_values: &[String], options: &GeneratorOptions) -> RustType {
+    let mut variants_text: String = "".to_string();
+    for (position, variant_name) in get_variant_names(enum_values).iter().enumerate() {
+        let default_text: &str = if position == 0 { "    #[default]\n" } else { "" };
+        variants_text += &format!("{}    #[serde(rename = {:?})]\n    {},\n", default_text, enum_values[position], variant_name);
+    }
+    let mut enum_type: RustType = RustType::new("enum", title, &["Clone", "Copy", "PartialEq", "Eq", "Hash", "Debug", "Serialize", "Deserialize", "Default"], options);
+    enum_type.body_text = format!(" {{\n{}}}\n", variants_text);
+    enum_type
+}
+
+/// convert a oneOf/anyOf told apart by a tag property to an internally tagged Rust enum, defaulting to its first branch
+fn get_tagged_enum_type(title: &str, tag_name: &str, branches: &[(String, Map<String, Value>)], draft: Draft, options: &GeneratorOptions) -> RustType {
+    let tag_values: Vec<String> = branches.iter().map(|(tag_value, _)| tag_value.clone()).collect();
+    let variant_names: Vec<String> = get_variant_names(&tag_values);
+    let mut enum_type: RustType = RustType::new("enum", title, &["Clone", "Serialize", "Deserialize"], options);
+    let mut variants_text: String = "".to_string();
+    for (position, (tag_value, branch)) in branches.iter().enumerate() {
+        let variant_type_name: String = get_field_type(&format!("{}[{}]", title, position), branch.clone(), draft, options);
+        variants_text += &format!("    #[serde(rename = {:?})]\n    {}({}),\n", tag_value, variant_names[position], variant_type_name);
+        enum_type.field_types.push(variant_type_name);
+    }
+    enum_type.attributes.push(format!("#[serde(tag = {:?})]", tag_name));
+    enum_type.body_text = format!(" {{\n{}}}\n", variants_text);
+    enum_type.default_field_types = enum_type.field_types[..1].to_vec();
+    enum_type.default_text = format!(r#"
+impl Default for {title} {{
+    fn default() -> Self {{
+        {title}::{first_variant}(Default::default())
+    }}
+}}
+"#, title = title, first_variant = variant_names[0]);
+    enum_type
+}
+
+/// Rust variant names for enum values, numbered where two values would otherwise share a name
+fn get_variant_names(enum_values: &[String]) -> Vec<String> {
+    let mut variant_names: Vec<String> = Vec::new();
+    for (position, enum_value) in enum_values.iter().enumerate() {
+        let mut variant_name: String = format_variant_name(enum_value);
+        if variant_names.contains(&variant_name) {
+            variant_name = format!("{}{}", variant_name, position);
+        }
+        variant_names.push(variant_name);
+    }
+    variant_names
+}
+
+/// convert an enum value to a Rust variant name in CamelCase, e.g. "user_created" to "UserCreated"
+pub(crate) fn format_variant_name(enum_value: &str) -> String {
+    let mut variant_name: String = enum_value
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let word: String = if word.chars().all(|c| !c.is_ascii_lowercase()) { word.to_lowercase() } else { word.to_string() };
+            word[0..1].to_uppercase() + &word[1..]
+        })
+        .collect();
+    if variant_name.is_empty() {
+        variant_name = "Empty".to_string();
+    }
+    if variant_name.starts_with(|c: char| c.is_ascii_digit()) {
+        variant_name = format!("V{}", variant_name);
+    }
+    variant_name
+}
+
+/// a transparent Rust newtype, for schemas that are not objects
+fn get_newtype_struct_type(title: &str, type_name: &str, options: &GeneratorOptions) -> RustType {
+    let mut newtype: RustType = RustType::new("struct", title, &["Clone", "Serialize", "Deserialize", "Default"], options);
+    newtype.attributes.push("#[serde(transparent)]".to_string());
+    newtype.body_text = format!("(pub {});\n", type_name);
+    newtype.field_types = vec![type_name.to_string()];
+    newtype.default_field_types = vec![type_name.to_string()];
+    newtype
+}
+
+/// json_schema() and add_json_schema_defs() for a generated type, giving back its schema with custom names
+/// and types applied, and the schemas of the types it refers to under $defs (or definitions) by type name
+fn get_json_schema_fn_text(title: &str, schema_json_map: &Map<String, Value>, source_title: Option<&Value>, pointer: &str, draft: Draft, options: &GeneratorOptions) -> String {
+    let mut own_schema: Map<String, Value> = schema_json_map.clone();
+    for keyword in ["$schema", "title", "x-rust-helper-of", "x-rust-pointer", "x-rust-optional", draft.id_keyword(), draft.defs_keyword()] {
+        // the types under $defs give their own schemas, and an $id would move what the $refs resolve against
+        own_schema.shift_remove(keyword);
+    }
+    if let Some(source_title) = source_title {
+        own_schema.insert("title".to_string(), source_title.clone());
+    }
+    if let Some(Value::Object(props_map)) = own_schema.get("properties") {
+        let mut new_props_map: Map<String, Value> = Map::new();
+        for (key_name, defn_value) in props_map {
+            let field_pointer: String = format!("{}/properties/{}", pointer, escape_pointer_token(key_name));
+            let field_name: String = get_custom_override(&options.custom_name_map, &field_pointer, key_name).cloned().unwrap_or_else(|| key_name.to_string());
+            let field_schema: Value = match get_custom_override(&options.custom_type_map, &field_pointer, key_name).and_then(|rust_type_name| get_rust_type_schema(rust_type_name)) {
+                Some(Value::Object(mut type_map)) => {
+                    // the type is overridden, but what the field means is not
+                    for keyword in ["title", "description"] {
+                        if let Some(annotation) = defn_value.get(keyword) {
+                            type_map.insert(keyword.to_string(), annotation.clone());
+                        }
+                    }
+                    Value::Object(type_map)
+                },
+                _                                 => defn_value.clone(),
+            };
+            new_props_map.insert(field_name, field_schema);
+        }
+        own_schema.insert("properties".to_string(), Value::Object(new_props_map));
+    }
+    if let Some(Value::Array(required_names)) = own_schema.get_mut("required") {
+        for required_name in required_names.iter_mut() {
+            if let Some(field_name) = required_name.as_str().and_then(|key_name| get_custom_override(&options.custom_name_map, &format!("{}/properties/{}", pointer, escape_pointer_token(key_name)), key_name)) {
+                *required_name = Value::String(field_name.to_string());
+            }
+        }
+    }
+    let mut referenced_types: Vec<String> = Vec::new();
+    let own_schema: Value = rename_refs(Value::Object(own_schema), draft, options, &mut referenced_types);
+    let add_referenced_text: String = referenced_types.iter()
+        .filter(|referenced_type| *referenced_type != title)
+        .map(|referenced_type| format!("        {}::add_json_schema_defs(defs);\n", referenced_type))
+        .collect();
+    // the generated code holds names and the schema as Rust string literals
+    let self_ref: String = format!("\"#/{}/{}\"", draft.defs_keyword(), title);
+    let title_literal: String = format!("{:?}", title);
+    let self_ref_literal: String = format!("{:?}", self_ref);
+    let schema_uri_literal: String = format!("{:?}", draft.schema_uri());
+    let defs_keyword_literal: String = format!("{:?}", draft.defs_keyword());
+    let own_schema_literal: String = format!("{:?}", own_schema.to_string());
+    format!(r#"impl {title} {{
+    /// the JSON Schema {title} was generated from, with the types it refers to under {defs_keyword}
+    pub fn json_schema() -> {serde_json}::Value {{
+        let mut defs = {serde_json}::Map::new();
+        Self::add_json_schema_defs(&mut defs);
+        let mut schema = defs.remove({title_literal}).unwrap();
+        if {serde_json}::Value::Object(defs.clone()).to_string().contains({self_ref_literal}) || schema.to_string().contains({self_ref_literal}) {{
+            defs.insert({title_literal}.to_string(), schema.clone());
+        }}
+        schema["$schema"] = {serde_json}::Value::String({schema_uri_literal}.to_string());
+        if !defs.is_empty() {{
+            schema[{defs_keyword_literal}] = {serde_json}::Value::Object(defs);
+        }}
+        schema
+    }}
+
+    /// add the JSON Schema of {title}, and of the types it refers to, to defs by type name
+    #[doc(hidden)]
+    pub fn add_json_schema_defs(defs: &mut {serde_json}::Map<String, {serde_json}::Value>) {{
+        if defs.contains_key({title_literal}) {{
+            return;
+        }}
+        defs.insert({title_literal}.to_string(), {serde_json}::from_str({own_schema_literal}).unwrap());
+{add_referenced_text}    }}
+}}
+"#, title = title, serde_json = options.serde_json_path(), defs_keyword = draft.defs_keyword(), title_literal = title_literal, self_ref_literal = self_ref_literal,
+        schema_uri_literal = schema_uri_literal, defs_keyword_literal = defs_keyword_literal,
+        own_schema_literal = own_schema_literal, add_referenced_text = add_referenced_text)
+}
+
+/// point the $refs in a schema at the Rust names of the types they refer to, noting each type
+fn rename_refs(schema_value: Value, draft: Draft, options: &GeneratorOptions, referenced_types: &mut Vec<String>) -> Value {
+    match schema_value {
+        Value::Object(schema_map) => Value::Object(schema_map.into_iter().map(|(keyword, keyword_value)| {
+            let defs_prefix: String = format!("#/{}/", draft.defs_keyword());
+            match (keyword.as_str(), &keyword_value) {
+                ("$ref", Value::String(ref_name)) if ref_name.starts_with(&defs_prefix) => {
+                    let referenced_type: String = format_struct_name(&ref_name[defs_prefix.len()..], &options.custom_name_map);
+                    if !referenced_types.contains(&referenced_type) {
+                        referenced_types.push(referenced_type.clone());
+                    }
+                    (keyword, Value::String(format!("{}{}", defs_prefix, referenced_type)))
+                },
+                _ => (keyword, rename_refs(keyword_value, draft, options, referenced_types)),
+            }
+        }).collect()),
+        Value::Array(schema_values) => Value::Array(schema_values.into_iter().map(|schema_value| rename_refs(schema_value, draft, options, referenced_types)).collect()),
+        _                           => schema_value,
+    }
+}
+
+/// the schema for a custom Rust type, when it is one the generator would give a schema, e.g. "i64" or "Option<String>"
+fn get_rust_type_schema(rust_type_name: &str) -> Option<Value> {
+    let rust_type_name: &str = rust_type_name.trim();
+    if let Some((generic_name, type_args)) = rust_type_name.strip_suffix('>').and_then(|rust_type_name| rust_type_name.split_once('<')) {
+        let generic_name: &str = generic_name.rsplit("::").next().unwrap_or(generic_name);
+        return match generic_name {
+            "Option"                => Some(serde_json::json!({ "anyOf": [get_rust_type_schema(type_args)?, { "type": "null" }] })),
+            "Vec"                   => Some(serde_json::json!({ "type": "array", "items": get_rust_type_schema(type_args)? })),
+            "BTreeSet" | "HashSet"  => Some(serde_json::json!({ "type": "array", "items": get_rust_type_schema(type_args)?, "uniqueItems": true })),
+            "HashMap" | "BTreeMap"  => {
+                let (key_type, value_type) = type_args.split_once(',')?;
+                if key_type.trim() != "String" {
+                    return None;
+                }
+                Some(serde_json::json!({ "type": "object", "additionalProperties": get_rust_type_schema(value_type)? }))
+            },
+            _                       => None,
+        };
+    }
+    for json_type_name in ["boolean", "number", "string", "integer", "null"] {
+        if get_simple_rust_type(json_type_name) == rust_type_name {
+            return Some(serde_json::json!({ "type": json_type_name }));
+        }
+    }
+    FORMAT_RUST_TYPES.iter()
+        .find(|(_, _, format_rust_type)| *format_rust_type == rust_type_name)
+        .map(|(json_type_name, format_name, _)| serde_json::json!({ "type": json_type_name, "format": format_name }))
+}
+
+/// integer and number formats with a sized Rust type
+const FORMAT_RUST_TYPES: &[(&str, &str, &str)] = &[
+    ("integer", "int8",   "i8"),
+    ("integer", "int16",  "i16"),
+    ("integer", "int32",  "i32"),
+    ("integer", "int64",  "i64"),
+    ("integer", "uint8",  "u8"),
+    ("integer", "uint16", "u16"),
+    ("integer", "uint32", "u32"),
+    ("integer", "uint64", "u64"),
+    ("number",  "float",  "f32"),
+    ("number",  "double", "f64"),
+];
+
+/// the sized Rust type for an integer or number format, e.g. "uint8" or "float"
+fn get_format_rust_type(json_type_name: &str, format_name: &str) -> Option<&'static str> {
+    FORMAT_RUST_TYPES.iter()
+        .find(|(format_json_type_name, format_format_name, _)| *format_json_type_name == json_type_name && *format_format_name == format_name)
+        .map(|(_, _, format_rust_type)| *format_rust_type)
+}
+
+/// convert JSON Schema types to Rust equivalents
+fn get_simple_rust_type(json_type_name: &str) -> String { 
+    let rust_type_name: &str = match json_type_name {
+        "boolean"    => "bool",
+        "number"     => "f64",
+        "string"     => "String",
+        "integer"    => "i32",
+        "null"       => "()",
+        _            => panic!("Could not parse JSON Schema, unknown type {}\n", json_type_name)
+    };
+    rust_type_name.to_string()
+}
+
+/// apply name changes, or else convert string to Capital Case 
+pub(crate) fn format_struct_name(src: &str, custom_name_map: &HashMap<String, String>) -> String {
+    if custom_name_map.contains_key(src) {
+        return custom_name_map.get(src).unwrap().to_string();
+    } 
+    let lc: String = src.to_string();
+    lc[0..1].to_uppercase() + &lc[1..]
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn process_example_file1() {
+        let file_path: String = "../examples/example.json".to_string();
+        let contents: String = fs::read_to_string(file_path)
+        .expect("Could not read example file\n");
+
+        let options: GeneratorOptions = GeneratorOptions::default();
+        let ts = json_schema_to_struct(&contents, &options).unwrap();
+        print!("{}\r\n", ts);
+        assert!(ts.contains("pub propertiesCount: ::serde_json::Value,"));
+    }
+
+    #[test]
+    fn process_example_file2() {
+        let file_path: String = "../examples/example2.json".to_string();
+        let contents: String = fs::read_to_string(file_path)
+            .expect("Could not read example file 2\n");
+
+        let options: GeneratorOptions = GeneratorOptions::default();
+        let ts = json_schema_to_struct(&contents, &options).unwrap();
+        print!("{}\r\n", ts);
+        assert!(ts.contains("pub struct Diet {"));
+        assert!(ts.contains("pub fruits: Vec<String>,"));
+        assert!(ts.contains("pub vegetables: Vec<Veggie>,"));
+        assert!(ts.contains("pub struct Veggie {"));
+        assert!(ts.contains("pub veggieLike: bool,"));
+    }
+
+    #[test]
+    fn process_example_file3_embedded_objs() {
+        let file_path: String = "../examples/example3.json".to_string();
+        let contents: String = fs::read_to_string(file_path)
+            .expect("Could not read example file 3\n");
+
+        let raw_json_val: Value = serde_json::from_str(&contents).unwrap();
+        if let Value::Object(raw_json) = raw_json_val {
+            let modified_json = process_embedded_objects_into_defs("People", &raw_json, Draft::DEFAULT, &GeneratorOptions::default());
+            print!("{}\r\n", serde_json::to_string(&modified_json).unwrap());
+            assert_eq!(modified_json["properties"]["results"]["items"]["$ref"], "#/$defs/People_results_item");
+            assert_eq!(modified_json["$defs"]["People_results_item"]["properties"]["name"]["$ref"], "#/$defs/People_results_item_name");
+            assert!(modified_json["$defs"]["People_results_item_name"]["properties"]["first"].is_object());
+        } else {
+            panic!("example file 3 is not a JSON object");
+        }
+    }
+
+    #[test]
+    fn process_example_file3() {
+        let file_path: String = "../examples/example3.json".to_string();
+        let contents: String = fs::read_to_string(file_path)
+            .expect("Could not read example file 3\n");
+
+        let mut options: GeneratorOptions = GeneratorOptions::default();
+        options.custom_name_map.insert("".to_string(), "People".to_string());
+        let ts = json_schema_to_struct(&contents, &options).unwrap();
+        print!("{}\r\n", ts);
+        assert!(ts.contains("pub struct People {"));
+        assert!(ts.contains("pub results: Vec<People_results_item>,"));
+        assert!(ts.contains("pub struct People_results_item {"));
+        assert!(ts.contains("pub dob: People_results_item_dob,"));
+    }
+
+    #[test]
+    fn process_example_file2_as_file() {
+        let file_path: String = "../examples/example2.json".to_string();
+        let mut options: GeneratorOptions = GeneratorOptions::default();
+        options.custom_name_map.insert("veggie".to_string(), "Vegetable".to_string());
+        options.custom_type_map.insert("veggieLike".to_string(), "i32".to_string());
+        let ts: String = json_schema_file_impl(file_path, &options);
+
+        print!("{}\r\n", ts);
+        assert!(ts.contains("pub vegetables: Vec<Vegetable>,"));
+        assert!(ts.contains("pub struct Vegetable {"));
+        assert!(ts.contains("pub veggieLike: i32,"));
+    }
+
+    #[test]
+    fn process_draft4_definitions() {
+        let contents: &str = r##"{
+            "$schema": "http://json-schema.org/draft-04/schema#",
+            "title": "order",
+            "type": "object",
+            "properties": {
+                "lines": { "type": "array", "items": { "$ref": "#/definitions/line" } },
+                "customer": { "type": "object", "properties": { "name": { "type": "string" } } }
+            },
+            "definitions": {
+                "line": { "type": "object", "properties": { "sku": { "type": "string" } } }
+            }
+        }"##;
+        let options: GeneratorOptions = GeneratorOptions::default();
+        let ts = json_schema_to_struct(contents, &options).unwrap();
+        print!("{}\r\n", ts);
+        assert!(ts.contains("pub lines: Vec<Line>,"));
+        assert!(ts.contains("pub struct Line {"));
+        assert!(ts.contains("pub customer: Order_customer,"));
+        assert!(ts.contains("pub struct Order_customer {"));
+
+        let raw_json: Value = serde_json::from_str(contents).unwrap();
+        let modified_json = process_embedded_objects_into_defs("Order", raw_json.as_object().unwrap(), Draft::Draft4, &GeneratorOptions::default());
+        assert_eq!(modified_json["properties"]["customer"]["$ref"], "#/definitions/Order_customer");
+        assert!(!modified_json.contains_key("$defs"));
+    }
+
+    #[test]
+    fn process_definitions_in_2020_12() {
+        let contents: &str = r##"{
+            "title": "a",
+            "type": "object",
+            "properties": {
+                "b": { "$ref": "#/definitions/b" },
+                "c": { "$ref": "#/$defs/c" }
+            },
+            "definitions": {
+                "b": { "type": "object", "properties": { "c": { "$ref": "#/$defs/c" } } }
+            },
+            "$defs": {
+                "c": { "type": "string", "enum": ["x", "y"] }
+            }
+        }"##;
+        let ts = json_schema_to_struct(contents, &GeneratorOptions::default()).unwrap();
+        print!("{}\r\n", ts);
+        assert!(ts.contains("pub b: B,"));
+        assert!(ts.contains("pub c: C,"));
+        assert!(ts.contains("pub struct B {"));
+        assert!(ts.contains("pub enum C {"));
+
+        // the def keeps its place in the document, for overrides by JSON Pointer
+        let options: GeneratorOptions = GeneratorOptions { custom_name_map: HashMap::from([("/definitions/b/properties/c".to_string(), "kind".to_string())]), ..GeneratorOptions::default() };
+        let ts = json_schema_to_struct(contents, &options).unwrap();
+        assert!(ts.contains("pub kind: C,"));
+    }
+
+    #[test]
+    fn process_defs_in_draft7() {
+        let contents: &str = r##"{
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "title": "a",
+            "type": "object",
+            "properties": {
+                "b": { "$ref": "#/$defs/b" },
+                "lines": { "type": "array", "items": { "$ref": "#/definitions/line" } }
+            },
+            "$defs": {
+                "b": { "type": "object", "properties": { "name": { "type": "string" } } }
+            },
+            "definitions": {
+                "line": { "type": "object", "properties": { "sku": { "type": "string" } } }
+            }
+        }"##;
+        let ts = json_schema_to_struct(contents, &GeneratorOptions::default()).unwrap();
+        print!("{}\r\n", ts);
+        assert!(ts.contains("pub b: B,"));
+        assert!(ts.contains("pub struct B {"));
+        assert!(ts.contains("pub lines: Vec<Line>,"));
+        assert!(ts.contains("pub struct Line {"));
+    }
+
+    #[test]
+    fn process_draft_override() {
+        // "definitions" is not the defs keyword of 2020-12, but its refs are still followed
+        let contents: &str = r##"{
+            "$schema": "http://json-schema.org/draft-04/schema#",
+            "title": "order",
+            "type": "object",
+            "properties": { "line": { "$ref": "#/definitions/line" } },
+            "definitions": { "line": { "type": "object", "properties": { "sku": { "type": "string" } } } }
+        }"##;
+        let options: GeneratorOptions = GeneratorOptions { draft_override: Draft::from_name("2020-12"), json_schema_fn: true, ..GeneratorOptions::default() };
+        let ts = json_schema_to_struct(contents, &options).unwrap();
+        print!("{}\r\n", ts);
+        assert!(ts.contains("pub line: Line,"));
+        assert!(ts.contains("schema[\"$defs\"] = ::serde_json::Value::Object(defs);"));
+        assert!(ts.contains("\"https://json-schema.org/draft/2020-12/schema\""));
+    }
+
+    #[test]
+    fn process_prefix_items() {
+        let contents: &str = r##"{
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "title": "feature",
+            "type": "object",
+            "properties": {
+                "pair": { "type": "array", "prefixItems": [{ "type": "number" }, { "type": "number" }], "items": false },
+                "single": { "type": "array", "prefixItems": [{ "type": "string" }], "maxItems": 1 },
+                "line": {
+                    "type": "array",
+                    "prefixItems": [{ "type": "string" }, { "type": "object", "properties": { "x": { "type": "integer" } } }],
+                    "items": { "type": "number" }
+                }
+            }
+        }"##;
+        let options: GeneratorOptions = GeneratorOptions::default();
+        let ts = json_schema_to_struct(contents, &options).unwrap();
+        print!("{}\r\n", ts);
+        assert!(ts.contains("pub pair: (f64, f64),"));
+        assert!(ts.contains("pub single: (String,),"));
+        assert!(ts.contains("pub line: Feature_line,"));
+        assert!(ts.contains("pub struct Feature_line(pub String, pub Feature_line_1, pub Vec<f64>);"));
+        assert!(ts.contains("impl<'de> ::serde::Deserialize<'de> for Feature_line {"));
+        assert!(ts.contains("pub struct Feature_line_1 {"));
+    }
+
+    #[test]
+    fn process_array_form_items() {
+        let contents: &str = r##"{
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "title": "position",
+            "type": "array",
+            "items": [{ "type": "number" }, { "type": "number" }],
+            "additionalItems": { "type": "number" }
+        }"##;
+        let options: GeneratorOptions = GeneratorOptions::default();
+        let ts = json_schema_to_struct(contents, &options).unwrap();
+        print!("{}\r\n", ts);
+        assert!(ts.contains("pub struct Position(pub f64, pub f64, pub Vec<f64>);"));
+        assert!(ts.contains("let item_1 = seq.next_element()?"));
+
+        let closed: &str = r##"{ "title": "point", "type": "array", "items": [{ "type": "number" }, { "type": "number" }], "additionalItems": false }"##;
+        let options: GeneratorOptions = GeneratorOptions { draft_override: Draft::from_name("draft-07"), ..GeneratorOptions::default() };
+        let ts = json_schema_to_struct(closed, &options).unwrap();
+        assert!(ts.contains("#[derive(Clone, ::serde::Serialize, ::serde::Deserialize, Default)]\r\npub struct Point(pub f64, pub f64);"));
+    }
+
+    #[test]
+    fn process_collections() {
+        let contents: &str = r##"{
+            "title": "tagged",
+            "type": "object",
+            "properties": {
+                "tags": { "type": "array", "uniqueItems": true, "items": { "type": "string" } },
+                "scores": { "type": "array", "uniqueItems": true, "items": { "type": "number" } },
+                "rgb": { "type": "array", "minItems": 3, "maxItems": 3, "items": { "type": "integer" } },
+                "owners": { "type": "array", "minItems": 1, "items": { "type": "string" } }
+            }
+        }"##;
+        let options: GeneratorOptions = GeneratorOptions::default();
+        let ts = json_schema_to_struct(contents, &options).unwrap();
+        assert!(ts.contains("pub tags: Vec<String>,"));
+        assert!(ts.contains("pub rgb: Vec<i32>,"));
+        assert!(ts.contains("pub owners: Vec<String>,"));
+
+        let options: GeneratorOptions = GeneratorOptions {
+            unique_items: Collection::BTreeSet,
+            fixed_items: Collection::Array,
+            min_items: Collection::NonEmpty,
+            ..GeneratorOptions::default()
+        };
+        let ts = json_schema_to_struct(contents, &options).unwrap();
+        print!("{}\r\n", ts);
+        assert!(ts.contains("pub tags: ::std::collections::BTreeSet<String>,"));
+        // f64 is not hashable, so stays a Vec
+        assert!(ts.contains("pub scores: Vec<f64>,"));
+        assert!(ts.contains("pub rgb: [i32; 3],"));
+        assert!(ts.contains("pub owners: Tagged_owners,"));
+        assert!(ts.contains("pub struct Tagged_owners(Vec<String>);"));
+        assert!(ts.contains("if items.is_empty() {"));
+
+        let mut options: GeneratorOptions = GeneratorOptions::default();
+        options.custom_collection_map.insert("tags".to_string(), Collection::HashSet);
+        options.custom_collection_map.insert("rgb".to_string(), Collection::Array);
+        let ts = json_schema_to_struct(contents, &options).unwrap();
+        assert!(ts.contains("pub tags: ::std::collections::HashSet<String>,"));
+        assert!(ts.contains("pub rgb: [i32; 3],"));
+        assert!(ts.contains("pub owners: Vec<String>,"));
+    }
+
+    #[test]
+    #[should_panic(expected = "not hashable")]
+    fn process_collection_not_hashable() {
+        let contents: &str = r##"{
+            "title": "tagged",
+            "type": "object",
+            "properties": {
+                "scores": { "type": "array", "items": { "type": "number" }, "x-rust-collection": "BTreeSet" }
+            }
+        }"##;
+        let _ = json_schema_to_struct(contents, &GeneratorOptions::default());
+    }
+
+    #[test]
+    fn process_json_schema_fn() {
+        let file_path: String = "../examples/example2.json".to_string();
+        let mut options: GeneratorOptions = GeneratorOptions { json_schema_fn: true, ..GeneratorOptions::default() };
+        options.custom_name_map.insert("veggie".to_string(), "Vegetable".to_string());
+        options.custom_type_map.insert("veggieLike".to_string(), "i64".to_string());
+        let ts: String = json_schema_file_impl(file_path, &options);
+
+        print!("{}\r\n", ts);
+        assert!(ts.contains("impl Diet {\n    /// the JSON Schema Diet was generated from, with the types it refers to under $defs\n    pub fn json_schema() -> ::serde_json::Value {"));
+        assert!(ts.contains("        Vegetable::add_json_schema_defs(defs);\n"));
+        assert!(ts.contains(r##"\"$ref\":\"#/$defs/Vegetable\""##));
+        assert!(ts.contains(r#"\"veggieLike\":{\"type\":\"integer\",\"format\":\"int64\",\"description\":\"Do I like this vegetable?\"}"#));
+        assert!(!ts.contains("$id"));
+    }
+
+    #[test]
+    fn process_serde_crate_path() {
+        let contents: &str = r##"{
+            "title": "event",
+            "type": "object",
+            "properties": {
+                "kind": { "enum": ["created", "deleted"] },
+                "payload": { "x-rust-type": "serde_json::Value" }
+            }
+        }"##;
+        let options: GeneratorOptions = GeneratorOptions {
+            serde_crate: Some("my_crate::serde".to_string()),
+            serde_json_crate: Some("my_crate::serde_json".to_string()),
+            ..GeneratorOptions::default()
+        };
+        let ts = json_schema_to_struct(contents, &options).unwrap();
+
+        print!("{}\r\n", ts);
+        assert!(ts.contains("#[derive(Clone, my_crate::serde::Serialize, my_crate::serde::Deserialize, Default)]\r\n#[serde(crate = \"my_crate::serde\")]\r\npub struct Event {"));
+        assert!(ts.contains("#[serde(crate = \"my_crate::serde\")]\r\npub enum Event_kind {"));
+        assert!(ts.contains("pub payload: my_crate::serde_json::Value,"));
+    }
+
+    #[test]
+    fn process_pointer_overrides() {
+        let contents: &str = r##"{
+            "title": "game",
+            "type": "object",
+            "properties": {
+                "seed": { "type": "integer" },
+                "info": {
+                    "type": "object",
+                    "properties": {
+                        "seed": { "type": "integer" },
+                        "name": { "type": "string" }
+                    }
+                },
+                "rounds": {
+                    "type": "array",
+                    "items": { "$ref": "#/$defs/round" }
+                }
+            },
+            "$defs": {
+                "round": {
+                    "type": "object",
+                    "properties": {
+                        "seed": { "type": "integer" },
+                        "score": { "type": "number" }
+                    }
+                }
+            }
+        }"##;
+        let mut options: GeneratorOptions = GeneratorOptions::default();
+        options.custom_type_map.insert("/properties/info/properties/seed".to_string(), "u64".to_string());
+        options.custom_type_map.insert("$defs.round.seed".to_string(), "i64".to_string());
+        options.custom_type_map.insert("/**/properties/score".to_string(), "f32".to_string());
+        options.custom_name_map.insert("*.name".to_string(), "info_name".to_string());
+        options.custom_name_map.insert("seed".to_string(), "game_seed".to_string());
+        let ts = json_schema_to_struct(contents, &options).unwrap();
+
+        print!("{}\r\n", ts);
+        assert!(ts.contains("pub struct Game {\n    #[serde(default)]\n    pub game_seed: i32,\n    #[serde(default)]\n    pub info: Game_info,"));
+        assert!(ts.contains("pub struct Game_info {\n    #[serde(default)]\n    pub game_seed: u64,\n    #[serde(default)]\n    pub info_name: String,"));
+        assert!(ts.contains("pub game_seed: i64,"));
+        assert!(ts.contains("pub score: f32,"));
+    }
+
+    #[test]
+    fn process_schema_order() {
+        let contents: &str = r##"{
+            "title": "order",
+            "type": "object",
+            "properties": {
+                "zeta": { "type": "object", "properties": { "second": { "type": "string" }, "first": { "type": "string" } } },
+                "alpha": { "type": "array", "items": { "type": "object", "properties": { "id": { "type": "integer" } } } },
+                "middle": { "enum": ["b", "a"] }
+            }
+        }"##;
+        let ts = json_schema_to_struct(contents, &GeneratorOptions::default()).unwrap();
+
+        print!("{}\r\n", ts);
+        assert!(ts.contains("pub zeta: Order_zeta,\n    #[serde(default)]\n    pub alpha: Vec<Order_alpha_item>,\n    #[serde(default)]\n    pub middle: Order_middle,"));
+        assert!(ts.contains("pub second: String,\n    #[serde(default)]\n    pub first: String,"));
+        let zeta_position: usize = ts.find("pub struct Order_zeta {").unwrap();
+        let alpha_position: usize = ts.find("pub struct Order_alpha_item {").unwrap();
+        let middle_position: usize = ts.find("pub enum Order_middle {").unwrap();
+        assert!(zeta_position < alpha_position && alpha_position < middle_position);
+        assert_eq!(ts, json_schema_to_struct(contents, &GeneratorOptions::default()).unwrap());
+    }
+
+    #[test]
+    fn process_builders() {
+        let contents: &str = r##"{
+            "title": "user",
+            "type": "object",
+            "required": ["email"],
+            "properties": {
+                "email": { "type": "string" },
+                "retries": { "type": "integer", "default": 3 },
+                "nickname": { "type": "string" }
+            }
+        }"##;
+        let options: GeneratorOptions = GeneratorOptions { builders: true, ..GeneratorOptions::default() };
+        let ts = json_schema_to_struct(contents, &options).unwrap();
+
+        print!("{}\r\n", ts);
+        assert!(ts.contains("impl User {\n    /// a builder, for setting the fields one at a time\n    pub fn builder() -> UserBuilder {"));
+        assert!(ts.contains("#[derive(Clone, Default)]\r\npub struct UserBuilder {\n    email: Option<String>,"));
+        assert!(ts.contains("pub fn email(mut self, value: impl Into<String>) -> Self {"));
+        assert!(ts.contains("email: self.email.ok_or_else(|| \"email is required\".to_string())?,"));
+        assert!(ts.contains("retries: self.retries.map_or_else(|| ::serde_json::from_str(\"3\").map_err(|error| format!(\"the default of retries does not fit: {}\", error)), Ok)?,"));
+        assert!(ts.contains("nickname: self.nickname.unwrap_or_default(),"));
+    }
+
+    #[test]
+    fn process_builder_constrained_fields() {
+        let contents: &str = r##"{
+            "title": "order",
+            "type": "object",
+            "required": ["count"],
+            "properties": {
+                "count": { "$ref": "#/$defs/count" },
+                "extra": { "$ref": "#/$defs/count" },
+                "size": { "$ref": "#/$defs/size" },
+                "offset": { "$ref": "#/$defs/offset" },
+                "quantity": { "$ref": "#/$defs/count", "default": 2 },
+                "note": { "$ref": "#/$defs/count", "readOnly": true }
+            },
+            "$defs": {
+                "count": { "type": "integer", "minimum": 1 },
+                "size": { "type": "integer", "minimum": 1, "default": 5 },
+                "offset": { "type": "integer", "minimum": 0 }
+            }
+        }"##;
+        let options: GeneratorOptions = GeneratorOptions { builders: true, ..GeneratorOptions::default() };
+        let ts = json_schema_to_struct(contents, &options).unwrap();
+
+        print!("{}\r\n", ts);
+        assert!(ts.contains("pub struct OrderBuilder {\n    count: Option<i32>,"));
+        assert!(ts.contains("pub fn count(mut self, value: impl Into<i32>) -> Self {"));
+        assert!(ts.contains("count: <Count as ::std::convert::TryFrom<i32>>::try_from(self.count.ok_or_else(|| \"count is required\".to_string())?)?,"));
+        // Count has no default, 0 not being one, so extra has to be set
+        assert!(ts.contains("extra: <Count as ::std::convert::TryFrom<i32>>::try_from(self.extra.ok_or_else(|| \"extra is required\".to_string())?)?,"));
+        assert!(ts.contains("size: self.size.map(<Size as ::std::convert::TryFrom<i32>>::try_from).transpose()?.unwrap_or_default(),"));
+        assert!(ts.contains("offset: self.offset.map(<Offset as ::std::convert::TryFrom<i32>>::try_from).transpose()?.unwrap_or_default(),"));
+        assert!(ts.contains("quantity: <Count as ::std::convert::TryFrom<i32>>::try_from(self.quantity.unwrap_or_else(|| ::serde_json::from_str(\"2\").unwrap()))?,"));
+        assert!(ts.contains("pub fn note(mut self, value: impl Into<Option<i32>>) -> Self {"));
+        assert!(ts.contains("note: self.note.flatten().map(<Count as ::std::convert::TryFrom<i32>>::try_from).transpose()?,"));
+    }
+
+    #[test]
+    #[should_panic(expected = "the default 0 of quantity does not meet its constraints")]
+    fn process_builder_invalid_default() {
+        let contents: &str = r##"{
+            "title": "order",
+            "type": "object",
+            "properties": {
+                "quantity": { "$ref": "#/$defs/count", "default": 0 }
+            },
+            "$defs": {
+                "count": { "type": "integer", "minimum": 1 }
+            }
+        }"##;
+        let options: GeneratorOptions = GeneratorOptions { builders: true, ..GeneratorOptions::default() };
+        let _ = json_schema_to_struct(contents, &options);
+    }
+
+    #[test]
+    #[should_panic(expected = "the default \"three\" of retries is not a i32")]
+    fn process_builder_mistyped_default() {
+        let contents: &str = r##"{
+            "title": "job",
+            "type": "object",
+            "properties": {
+                "retries": { "type": "integer", "default": "three" }
+            }
+        }"##;
+        let options: GeneratorOptions = GeneratorOptions { builders: true, ..GeneratorOptions::default() };
+        let _ = json_schema_to_struct(contents, &options);
+    }
+
+    #[test]
+    fn process_constrained_scalars() {
+        let contents: &str = r##"{
+            "title": "order",
+            "type": "object",
+            "properties": {
+                "code": { "$ref": "#/$defs/code" },
+                "count": { "$ref": "#/$defs/count" },
+                "label": { "$ref": "#/$defs/label" }
+            },
+            "$defs": {
+                "code": { "type": "string", "pattern": "^[A-Z]{3}$", "maxLength": 3 },
+                "count": { "type": "integer", "minimum": 1, "default": 1 },
+                "label": { "type": "string", "pattern": "^[a-z]+$" }
+            }
+        }"##;
+        let options: GeneratorOptions = GeneratorOptions { regex_crate: Some("::regex".to_string()), ..GeneratorOptions::default() };
+        let ts = json_schema_to_struct(contents, &options).unwrap();
+
+        print!("{}\r\n", ts);
+        assert!(ts.contains("#[derive(Clone)]\r\npub struct Code(String);"));
+        assert!(ts.contains("impl ::std::convert::TryFrom<String> for Code {"));
+        assert!(ts.contains("if value.chars().count() > 3 {\n            return Err(format!(\"{} {}\", value, \"must be at most 3 characters long\"));"));
+        assert!(ts.contains("static PATTERN: ::std::sync::OnceLock<::regex::Regex> = ::std::sync::OnceLock::new(); PATTERN.get_or_init(|| ::regex::Regex::new(\"^[A-Z]{3}$\").unwrap()) }.is_match(&value) {"));
+        assert!(ts.contains("impl ::std::ops::Deref for Code {\n    type Target = String;"));
+        assert!(ts.contains("impl ::std::fmt::Display for Count {"));
+        assert!(ts.contains("if (value as f64) < 1.0 {"));
+        assert!(ts.contains("#[derive(Clone)]\r\npub struct Count(i32);"));
+        assert!(ts.contains("Count(::serde_json::from_str(\"1\").unwrap())"));
+        assert!(ts.contains("<Count as ::std::convert::TryFrom<i32>>::try_from(value).map_err(::serde::de::Error::custom)"));
+        // "" is not a valid code, so neither a code nor an order has a default
+        assert!(ts.contains("#[derive(Clone, ::serde::Serialize, ::serde::Deserialize)]\r\npub struct Order {\n    pub code: Code,"));
+        assert!(ts.contains("    #[serde(default)]\n    pub count: Count,"));
+    }
+
+    #[test]
+    fn process_constrained_scalar_zero_default() {
+        let contents: &str = r##"{
+            "title": "page",
+            "type": "object",
+            "properties": {
+                "offset": { "$ref": "#/$defs/offset" }
+            },
+            "$defs": {
+                "offset": { "type": "integer", "minimum": 0, "maximum": 100 }
+            }
+        }"##;
+        let ts = json_schema_to_struct(contents, &GeneratorOptions::default()).unwrap();
+
+        print!("{}\r\n", ts);
+        assert!(ts.contains("#[derive(Clone, Default)]\r\npub struct Offset(i32);"));
+        assert!(ts.contains("::serde::Deserialize, Default)]\r\npub struct Page {\n    #[serde(default)]\n    pub offset: Offset,"));
+    }
+
+    #[test]
+    #[should_panic(expected = "the default 0 of Count does not meet its constraints")]
+    fn process_constrained_scalar_invalid_default() {
+        let contents: &str = r##"{ "title": "count", "type": "integer", "minimum": 1, "default": 0 }"##;
+        let _ = json_schema_to_struct(contents, &GeneratorOptions::default());
+    }
+
+    #[test]
+    fn process_pattern_without_regex() {
+        // without the regex crate the newtype is made all the same, and says the pattern is not checked
+        let contents: &str = r##"{
+            "title": "order",
+            "type": "object",
+            "properties": {
+                "code": { "$ref": "#/$defs/code" },
+                "label": { "$ref": "#/$defs/label" }
+            },
+            "$defs": {
+                "code": { "type": "string", "pattern": "^[A-Z]{3}$", "maxLength": 3 },
+                "label": { "type": "string", "pattern": "^[a-z]+$" }
+            }
+        }"##;
+        let ts = json_schema_to_struct(contents, &GeneratorOptions::default()).unwrap();
+
+        print!("{}\r\n", ts);
+        assert!(ts.contains("/// the pattern ^[A-Z]{3}$ is not checked, as no path to the regex crate was given with $regex\r\n#[derive(Clone)]\r\npub struct Code(String);"));
+        assert!(ts.contains("if value.chars().count() > 3 {"));
+        assert!(ts.contains("/// the pattern ^[a-z]+$ is not checked, as no path to the regex crate was given with $regex\r\n#[derive(Clone, ::serde::Serialize, ::serde::Deserialize, Default)]\r\n#[serde(transparent)]\r\npub struct Label(pub String);"));
+        assert!(!ts.contains("Regex"));
+    }
+
+    #[test]
+    fn process_free_form_objects() {
+        let contents: &str = r##"{
+            "title": "event",
+            "type": "object",
+            "properties": {
+                "labels": { "type": "object" },
+                "extra": { "type": "object", "additionalProperties": true },
+                "payload": { "description": "anything" },
+                "counts": { "type": "object", "additionalProperties": { "type": "integer" } },
+                "context": { "$ref": "#/$defs/context" }
+            },
+            "$defs": {
+                "context": { "type": "object", "additionalProperties": {} }
+            }
+        }"##;
+        let ts = json_schema_to_struct(contents, &GeneratorOptions::default()).unwrap();
+
+        print!("{}\r\n", ts);
+        assert!(ts.contains("pub labels: ::serde_json::Map<String, ::serde_json::Value>,"));
+        assert!(ts.contains("pub extra: ::serde_json::Map<String, ::serde_json::Value>,"));
+        assert!(ts.contains("pub payload: ::serde_json::Value,"));
+        assert!(ts.contains("pub counts: ::std::collections::HashMap<String, i32>,"));
+        assert!(ts.contains("pub struct Context(pub ::serde_json::Map<String, ::serde_json::Value>);"));
+
+        let options: GeneratorOptions = GeneratorOptions { free_form_object: Some("BTreeMap".to_string()), ..GeneratorOptions::default() };
+        let ts = json_schema_to_struct(contents, &options).unwrap();
+        assert!(ts.contains("pub labels: ::std::collections::BTreeMap<String, ::serde_json::Value>,"));
+        assert!(ts.contains("pub counts: ::std::collections::BTreeMap<String, i32>,"));
+    }
+
+    #[test]
+    fn process_conditional_schemas() {
+        let contents: &str = r##"{
+            "title": "payment",
+            "type": "object",
+            "required": ["amount"],
+            "properties": {
+                "amount": { "type": "integer" },
+                "name": { "type": "string" },
+                "credit_card": { "type": "string" },
+                "currency": { "type": "string" },
+                "method": {
+                    "type": "object",
+                    "properties": { "kind": { "type": "string", "enum": ["card", "cash"] } },
+                    "if": { "properties": { "kind": { "const": "card" } } },
+                    "then": { "properties": { "last_digits": { "type": "string" } }, "required": ["last_digits"] },
+                    "else": { "properties": { "change": { "type": "integer" } } }
+                }
+            },
+            "dependentRequired": { "credit_card": ["name"] },
+            "dependentSchemas": { "name": { "properties": { "billing_address": { "type": "string" } } } },
+            "if": { "required": ["currency"] },
+            "then": { "required": ["amount"] }
+        }"##;
+        let ts = json_schema_to_struct(contents, &GeneratorOptions::default()).unwrap();
+
+        print!("{}\r\n", ts);
+        assert!(ts.contains("    #[serde(default)]\n    pub amount: i32,"));
+        assert!(ts.contains("    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n    pub credit_card: Option<String>,"));
+        assert!(ts.contains("    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n    pub name: Option<String>,"));
+        assert!(ts.contains("    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n    pub billing_address: Option<String>,"));
+        assert!(ts.contains("    #[serde(default)]\n    pub currency: String,"));
+        assert!(ts.contains("#[serde(tag = \"kind\")]\r\npub enum Payment_method {\n    #[serde(rename = \"card\")]\n    Card(Payment_method_Card),\n    #[serde(rename = \"cash\")]\n    Cash(Payment_method_Cash),"));
+        assert!(ts.contains("pub struct Payment_method_Card {\n    #[serde(default)]\n    pub last_digits: String,\n}"));
+        assert!(ts.contains("pub struct Payment_method_Cash {\n    #[serde(default)]\n    pub change: i32,\n}"));
+
+        // if, then and else only mean something from draft 7 on
+        let ts = json_schema_to_struct(&contents.replace("\"title\": \"payment\",", "\"$schema\": \"http://json-schema.org/draft-06/schema#\", \"title\": \"payment\","), &GeneratorOptions::default()).unwrap();
+        assert!(ts.contains("pub struct Payment_method {\n    #[serde(default)]\n    pub kind: Payment_method_kind,\n}"));
+    }
+
+    #[test]
+    fn process_conditional_union_dependencies() {
+        let contents: &str = r##"{
+            "title": "p",
+            "type": "object",
+            "properties": {
+                "kind": { "type": "string", "enum": ["card", "cash"] },
+                "a": { "type": "string" },
+                "b": { "type": "string" }
+            },
+            "dependentRequired": { "a": ["b"] },
+            "dependentSchemas": { "b": { "properties": { "c": { "type": "string" } } } },
+            "if": { "properties": { "kind": { "const": "card" } } },
+            "then": { "properties": { "last_digits": { "type": "string" } }, "required": ["last_digits"] }
+        }"##;
+        let ts = json_schema_to_struct(contents, &GeneratorOptions::default()).unwrap();
+
+        print!("{}\r\n", ts);
+        assert!(ts.contains("#[serde(tag = \"kind\")]\r\npub enum P {"));
+        for branch_name in ["P_Card", "P_Cash"] {
+            let branch_text: &str = &ts[ts.find(&format!("pub struct {} {{", branch_name)).unwrap()..];
+            let branch_text: &str = &branch_text[..branch_text.find("\n}").unwrap()];
+            assert!(branch_text.contains("    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n    pub a: Option<String>,"));
+            assert!(branch_text.contains("    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n    pub b: Option<String>,"));
+            assert!(branch_text.contains("    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n    pub c: Option<String>,"));
+        }
+        assert!(ts.contains("    #[serde(default)]\n    pub last_digits: String,"));
+    }
+
+    #[test]
+    fn process_root_pointers() {
+        let contents: &str = r##"{
+            "title": "notebook",
+            "type": "object",
+            "properties": {
+                "cells": { "type": "array", "items": { "$ref": "#/$defs/cell" } }
+            },
+            "$defs": {
+                "cell": {
+                    "type": "object",
+                    "properties": {
+                        "source": { "type": "string" },
+                        "outputs": { "type": "array", "items": { "$ref": "#/$defs/output" } }
+                    }
+                },
+                "output": { "type": "object", "properties": { "text": { "type": "string" } } },
+                "kernel": { "type": "object", "properties": { "name": { "type": "string" } } },
+                "unused": { "type": "array" }
+            }
+        }"##;
+        let options: GeneratorOptions = GeneratorOptions { root_pointers: vec!["/$defs/cell".to_string()], ..GeneratorOptions::default() };
+        let ts = json_schema_to_struct(contents, &options).unwrap();
+
+        print!("{}\r\n", ts);
+        assert!(ts.contains("pub struct Cell {"));
+        assert!(ts.contains("pub struct Output {"));
+        assert!(!ts.contains("pub struct Notebook {"));
+        assert!(!ts.contains("pub struct Kernel {"));
+
+        let options: GeneratorOptions = GeneratorOptions { root_pointers: vec!["".to_string(), "#/$defs/kernel".to_string()], ..GeneratorOptions::default() };
+        let ts = json_schema_to_struct(contents, &options).unwrap();
+        assert!(ts.contains("pub struct Notebook {"));
+        assert!(ts.contains("pub struct Cell {"));
+        assert!(ts.contains("pub struct Output {"));
+        assert!(ts.contains("pub struct Kernel {"));
+        assert!(!ts.contains("Unused"));
+    }
+
+    #[test]
+    #[should_panic(expected = "no definition at /$defs/missing")]
+    fn process_root_pointer_missing() {
+        let options: GeneratorOptions = GeneratorOptions { root_pointers: vec!["/$defs/missing".to_string()], ..GeneratorOptions::default() };
+        json_schema_to_struct(r#"{ "title": "empty", "type": "object", "properties": {}, "$defs": {} }"#, &options).unwrap();
+    }
+
+    #[test]
+    fn process_file_pointer() {
+        let ts: String = json_schema_file_pointer_impl("../examples/example2.json".to_string(), "/$defs/veggie", Some("Vegetable"), &GeneratorOptions::default());
+
+        print!("{}\r\n", ts);
+        assert!(ts.contains("pub struct Vegetable {"));
+        assert!(ts.contains("pub veggieName: String,"));
+        assert!(!ts.contains("pub struct Diet"));
+
+        let ts: String = json_schema_file_pointer_impl("../examples/example2.json".to_string(), "/$defs/veggie", None, &GeneratorOptions::default());
+        assert!(ts.contains("pub struct Veggie {"));
+    }
+
+    #[test]
+    fn process_schema_dir() {
+        let ts: String = json_schema_dir_impl("../examples/schemas".to_string(), "*.json", &GeneratorOptions::default());
+
+        print!("{}\r\n", ts);
+        assert!(ts.contains("pub mod address {\n"));
+        assert!(ts.contains("pub mod customer {\n"));
+        assert!(ts.contains("pub mod order_line {\n"));
+        assert!(ts.contains("pub billing: super::address::Address,"));
+        assert!(ts.contains("pub shipping: Vec<super::address::Address>,"));
+        assert!(ts.contains("pub country: super::address::Country,"));
+        assert!(ts.contains("pub struct Order_line {"));
+        assert!(ts.contains("pub customer: super::customer::Customer,"));
+    }
+
+    #[test]
+    #[should_panic(expected = "the type Customer is generated from both")]
+    fn process_schema_dir_duplicate_types() {
+        let dir_path: std::path::PathBuf = std::env::temp_dir().join(format!("json_schema_dir_{}", std::process::id()));
+        fs::create_dir_all(&dir_path).unwrap();
+        let schema_text: &str = r#"{ "title": "customer", "type": "object", "properties": { "name": { "type": "string" } } }"#;
+        fs::write(dir_path.join("customer.json"), schema_text).unwrap();
+        fs::write(dir_path.join("client.json"), schema_text).unwrap();
+        json_schema_dir_impl(dir_path.to_string_lossy().to_string(), "*.json", &GeneratorOptions::default());
+    }
+
+    #[test]
+    fn process_module_visibility() {
+        let file_path: String = "../examples/example3.json".to_string();
+        let mut options: GeneratorOptions = GeneratorOptions {
+            module_name: Some("people".to_string()),
+            visibility: Some("pub(crate)".to_string()),
+            ..GeneratorOptions::default()
+        };
+        options.custom_name_map.insert("".to_string(), "People".to_string());
+        let ts: String = json_schema_file_impl(file_path, &options);
+
+        print!("{}\r\n", ts);
+        assert!(ts.starts_with("pub(crate) mod people {\n"));
+        assert!(ts.contains("use super::*;\n"));
+        assert!(ts.contains("/// part of People, not a type of its own in the schema\r\n#[derive(Clone, ::serde::Serialize, ::serde::Deserialize, Default)]\r\npub struct People_results_item {"));
+        assert!(ts.contains("\n}\n\nconst _: &[u8] = include_bytes!("));
+        assert!(ts.trim_end().ends_with("/examples/example3.json\");"));
+
+        options.module_name = None;
+        options.visibility = Some("".to_string());
+        let ts: String = json_schema_file_impl("../examples/example3.json".to_string(), &options);
+        assert!(ts.contains("\nstruct People_results_item {"));
+        assert!(!ts.contains("pub struct"));
+    }
+
+    #[test]
+    fn process_extra_derives() {
+        let contents: &str = r##"{
+            "title": "order",
+            "type": "object",
+            "properties": {
+                "id": { "type": "integer" },
+                "lines": { "type": "array", "items": { "$ref": "#/$defs/line" } },
+                "labels": { "type": "object", "additionalProperties": { "type": "string" } },
+                "customer": { "type": "object", "properties": { "name": { "type": "string" } } }
+            },
+            "$defs": {
+                "line": { "type": "object", "properties": { "sku": { "type": "string" }, "price": { "type": "number" } } }
+            }
+        }"##;
+        let mut options: GeneratorOptions = GeneratorOptions {
+            extra_derives: vec!["Debug".to_string(), "PartialEq".to_string(), "Eq".to_string(), "Hash".to_string()],
+            extra_attributes: vec!["#[serde(deny_unknown_fields)]".to_string()],
+            ..GeneratorOptions::default()
+        };
+        options.custom_derive_map.insert("Order_customer".to_string(), vec!["PartialOrd".to_string(), "Ord".to_string()]);
+        let ts = json_schema_to_struct(contents, &options).unwrap();
+
+        print!("{}\r\n", ts);
+        assert!(ts.contains("#[derive(Clone, ::serde::Serialize, ::serde::Deserialize, Default, Debug, PartialEq)]\r\n#[serde(deny_unknown_fields)]\r\npub struct Order {"));
+        assert!(ts.contains("#[derive(Clone, ::serde::Serialize, ::serde::Deserialize, Default, Debug, PartialEq)]\r\n#[serde(deny_unknown_fields)]\r\npub struct Line {"));
+        assert!(ts.contains("#[derive(Clone, ::serde::Serialize, ::serde::Deserialize, Default, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]\r\n#[serde(deny_unknown_fields)]\r\npub struct Order_customer {"));
+    }
+
+}
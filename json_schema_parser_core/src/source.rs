@@ -0,0 +1,228 @@
+//! Copyright (c) 2023  Ward van der Veer
+
+extern crate serde_json;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde_json::Value;
+
+/// the format a schema document is written in
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum SourceFormat {
+    #[default]
+    Json,
+    Yaml,
+    Json5,
+}
+
+impl SourceFormat {
+    /// recognise a format name as used for overrides, e.g. "yaml", "yml", "json5"
+    pub fn from_name(name: &str) -> Option<SourceFormat> {
+        match name.trim().trim_start_matches('.').to_lowercase().as_str() {
+            "json"         => Some(SourceFormat::Json),
+            "yaml" | "yml" => Some(SourceFormat::Yaml),
+            "json5"        => Some(SourceFormat::Json5),
+            _              => None,
+        }
+    }
+
+    /// the name of the format as used in messages
+    pub fn name(self) -> &'static str {
+        match self {
+            SourceFormat::Json  => "JSON",
+            SourceFormat::Yaml  => "YAML",
+            SourceFormat::Json5 => "JSON5",
+        }
+    }
+
+    /// the format given by a file's extension, JSON for any extension not recognised
+    pub fn from_path(file_path: &str) -> SourceFormat {
+        Path::new(file_path).extension()
+            .and_then(|extension| SourceFormat::from_name(&extension.to_string_lossy()))
+            .unwrap_or(SourceFormat::Json)
+    }
+}
+
+/// parse a document in the given format to JSON,
+/// panicking with the line and column of any error in the named source
+pub fn parse_document(text: &str, format: SourceFormat, source_name: &str) -> Value {
+    let parsed: Result<Value, (String, Option<(usize, usize)>)> = match format {
+        SourceFormat::Json  => serde_json::from_str(text).map_err(|err| {
+            // serde_json puts the location at the end of its message
+            let message: String = err.to_string();
+            let message: String = message.rsplit_once(" at line ").map(|(message, _)| message.to_string()).unwrap_or(message);
+            (message, Some((err.line(), err.column())))
+        }),
+        SourceFormat::Yaml  => parse_yaml(text),
+        SourceFormat::Json5 => parse_json5(text),
+    };
+    match parsed {
+        Ok(value)                              => value,
+        Err((message, Some((line, column))))   => panic!("Could not parse {} in {} at line {} column {}: {}\n", format.name(), source_name, line, column, message),
+        Err((message, None))                   => panic!("Could not parse {} in {}: {}\n", format.name(), source_name, message),
+    }
+}
+
+/// the path of a schema file given to a macro: relative to the Cargo.toml of the crate using the macro, or with
+/// "$OUT_DIR/" to the build script output directory, rather than to wherever the compiler happens to run
+pub fn resolve_file_path(file_path: &str) -> String {
+    if let Some(out_dir_path) = file_path.strip_prefix("$OUT_DIR") {
+        let out_dir: String = env::var("OUT_DIR")
+            .unwrap_or_else(|_| panic!("Could not read {}, OUT_DIR is not set (is there a build script?)\n", file_path));
+        return Path::new(&out_dir).join(out_dir_path.trim_start_matches(['/', '\\'])).to_string_lossy().to_string();
+    }
+    match env::var("CARGO_MANIFEST_DIR") {
+        Ok(manifest_dir) if Path::new(file_path).is_relative() => Path::new(&manifest_dir).join(file_path).to_string_lossy().to_string(),
+        _                                                      => file_path.to_string(),
+    }
+}
+
+/// read a schema file given to a macro, panicking with the path that was tried if it can't be read
+pub fn read_schema_file(file_path: &str, kind: &str) -> (String, String) {
+    let resolved_path: String = resolve_file_path(file_path);
+    match fs::read_to_string(&resolved_path) {
+        Ok(text) => (resolved_path, text),
+        Err(err) => panic!("Could not read {} file: {} (tried {}: {})\n", kind, file_path, resolved_path, err),
+    }
+}
+
+/// an item for the generated code that includes the file read, so that cargo rebuilds when it changes
+pub fn get_file_dependency_text(file_path: &str) -> String {
+    // include_bytes! resolves relative paths against the source file of the macro call, so the path is made absolute
+    let absolute_path: PathBuf = fs::canonicalize(file_path).unwrap_or_else(|_| PathBuf::from(file_path));
+    format!("\nconst _: &[u8] = include_bytes!({:?});\n", absolute_path.to_string_lossy())
+}
+
+/// parse a YAML document to JSON
+#[cfg(feature = "yaml")]
+fn parse_yaml(text: &str) -> Result<Value, (String, Option<(usize, usize)>)> {
+    match serde_yaml::from_str::<serde_yaml::Value>(text) {
+        Ok(yaml_value) => Ok(yaml_to_json(yaml_value)),
+        Err(err)       => {
+            let location: Option<(usize, usize)> = err.location().map(|location| (location.line(), location.column()));
+            let message: String = err.to_string();
+            // serde_yaml puts the location at the end of its message
+            let message: String = message.rsplit_once(" at line ").map(|(message, _)| message.to_string()).unwrap_or(message);
+            Err((message, location))
+        },
+    }
+}
+
+/// parse a YAML document to JSON, which needs the yaml feature
+#[cfg(not(feature = "yaml"))]
+fn parse_yaml(_text: &str) -> Result<Value, (String, Option<(usize, usize)>)> {
+    Err(("YAML needs the yaml feature".to_string(), None))
+}
+
+/// convert YAML to JSON, keeping non-string keys such as response codes as strings
+#[cfg(feature = "yaml")]
+fn yaml_to_json(yaml_value: serde_yaml::Value) -> Value {
+    match yaml_value {
+        serde_yaml::Value::Null           => Value::Null,
+        serde_yaml::Value::Bool(b)        => Value::Bool(b),
+        serde_yaml::Value::Number(n)      => serde_json::to_value(n).unwrap_or(Value::Null),
+        serde_yaml::Value::String(s)      => Value::String(s),
+        serde_yaml::Value::Sequence(seq)  => Value::Array(seq.into_iter().map(yaml_to_json).collect()),
+        serde_yaml::Value::Mapping(map)   => Value::Object(map.into_iter().map(|(key, value)| {
+            let key_text: String = match key {
+                serde_yaml::Value::String(s) => s,
+                other                        => serde_yaml::to_string(&other).unwrap_or_default().trim_end().to_string(),
+            };
+            (key_text, yaml_to_json(value))
+        }).collect()),
+        serde_yaml::Value::Tagged(tagged) => yaml_to_json(tagged.value),
+    }
+}
+
+/// parse a JSON5 document to JSON
+#[cfg(feature = "json5")]
+fn parse_json5(text: &str) -> Result<Value, (String, Option<(usize, usize)>)> {
+    json5::from_str(text).map_err(|err| {
+        let json5::Error::Message { msg, location } = err;
+        (msg, location.map(|location| (location.line, location.column)))
+    })
+}
+
+/// parse a JSON5 document to JSON, which needs the json5 feature
+#[cfg(not(feature = "json5"))]
+fn parse_json5(_text: &str) -> Result<Value, (String, Option<(usize, usize)>)> {
+    Err(("JSON5 needs the json5 feature".to_string(), None))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn source_format_from_path() {
+        assert_eq!(SourceFormat::from_path("schemas/user.yaml"), SourceFormat::Yaml);
+        assert_eq!(SourceFormat::from_path("schemas/user.YML"), SourceFormat::Yaml);
+        assert_eq!(SourceFormat::from_path("schemas/user.json5"), SourceFormat::Json5);
+        assert_eq!(SourceFormat::from_path("schemas/user.schema.json"), SourceFormat::Json);
+        assert_eq!(SourceFormat::from_path("schemas/user"), SourceFormat::Json);
+    }
+
+    #[test]
+    fn file_dependency_text() {
+        let dependency_text: String = get_file_dependency_text("../examples/example2.json");
+        print!("{}\r\n", dependency_text);
+        assert!(dependency_text.starts_with("\nconst _: &[u8] = include_bytes!(\"/"));
+        assert!(dependency_text.ends_with("/examples/example2.json\");\n"));
+    }
+
+    #[test]
+    fn resolve_manifest_relative_path() {
+        let resolved_path: String = resolve_file_path("src/source.rs");
+        assert!(Path::new(&resolved_path).is_absolute());
+        assert!(resolved_path.ends_with("json_schema_parser_core/src/source.rs"));
+        assert_eq!(resolve_file_path("/schemas/user.json"), "/schemas/user.json");
+    }
+
+    #[test]
+    #[should_panic(expected = "tried ")]
+    fn read_missing_schema_file() {
+        read_schema_file("schemas/missing.json", "JSON Schema");
+    }
+
+    #[test]
+    #[should_panic(expected = "Could not read $OUT_DIR/user.json, OUT_DIR is not set")]
+    fn resolve_out_dir_path_without_build_script() {
+        resolve_file_path("$OUT_DIR/user.json");
+    }
+
+    #[test]
+    #[should_panic(expected = "in user.json at line 3 column 5")]
+    fn parse_json_error_location() {
+        parse_document("{\n  \"title\": \"user\",\n    }", SourceFormat::Json, "user.json");
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn parse_yaml_document() {
+        let value: Value = parse_document("title: user\ntype: object\nproperties:\n  id: { type: integer }\n", SourceFormat::Yaml, "user.yaml");
+        assert_eq!(value, serde_json::json!({ "title": "user", "type": "object", "properties": { "id": { "type": "integer" } } }));
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    #[should_panic(expected = "in user.yaml at line 3 column")]
+    fn parse_yaml_error_location() {
+        parse_document("title: user\ntype: object\n  properties: {}\n", SourceFormat::Yaml, "user.yaml");
+    }
+
+    #[cfg(feature = "json5")]
+    #[test]
+    fn parse_json5_document() {
+        let value: Value = parse_document("// a user\n{ title: 'user', type: \"object\", properties: { id: { type: 'integer', }, }, }", SourceFormat::Json5, "user.json5");
+        assert_eq!(value, serde_json::json!({ "title": "user", "type": "object", "properties": { "id": { "type": "integer" } } }));
+    }
+
+    #[cfg(feature = "json5")]
+    #[test]
+    #[should_panic(expected = "in user.json5 at line 2 column")]
+    fn parse_json5_error_location() {
+        parse_document("// a user\n{ title: 'user' type: 'object' }", SourceFormat::Json5, "user.json5");
+    }
+
+}
@@ -0,0 +1,709 @@
+//! Copyright (c) 2023  Ward van der Veer
+
+extern crate serde_json;
+use draft::Draft;
+use regex::Regex;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// base URI used for schemas that do not declare their own id
+const DEFAULT_BASE_URI: &str = "json-schema:///root.json";
+
+/// a single failed keyword, located by JSON Pointers into the instance and the schema
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValidationError {
+    pub instance_path: String,
+    pub schema_path: String,
+    pub message: String,
+}
+
+/// validate a JSON instance against a JSON Schema, following the draft named by its $schema
+pub fn validate(schema: &Value, instance: &Value) -> Result<(), Vec<ValidationError>> {
+    validate_with_remotes(schema, instance, &HashMap::new(), None)
+}
+
+/// validate a JSON instance against a JSON Schema, resolving remote $refs from the supplied documents,
+/// and optionally overriding the draft named by $schema
+pub fn validate_with_remotes(schema: &Value, instance: &Value, remotes: &HashMap<String, Value>, draft_override: Option<Draft>) -> Result<(), Vec<ValidationError>> {
+    let draft = Draft::detect(schema, draft_override);
+    let mut index: HashMap<String, &Value> = HashMap::new();
+    for (remote_uri, remote_schema) in remotes {
+        let remote_base = strip_fragment(remote_uri).to_string();
+        index_schema_ids(remote_schema, &remote_base, draft, &mut index);
+    }
+    index_schema_ids(schema, DEFAULT_BASE_URI, draft, &mut index);
+    let validator = Validator { draft, index };
+    let mut errors: Vec<ValidationError> = Vec::new();
+    validator.validate_node(schema, instance, DEFAULT_BASE_URI, "", "", &mut errors);
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// the draft in force, and schema documents and embedded subschemas keyed by absolute URI
+struct Validator<'a> {
+    draft: Draft,
+    index: HashMap<String, &'a Value>,
+}
+
+impl<'a> Validator<'a> {
+    /// check whether an instance is valid without collecting errors
+    fn is_valid(&self, schema: &'a Value, instance: &Value, base_uri: &str) -> bool {
+        let mut errors: Vec<ValidationError> = Vec::new();
+        self.validate_node(schema, instance, base_uri, "", "", &mut errors);
+        errors.is_empty()
+    }
+
+    /// validate an instance against one (sub)schema
+    fn validate_node(&self, schema: &'a Value, instance: &Value, base_uri: &str, instance_path: &str, schema_path: &str, errors: &mut Vec<ValidationError>) {
+        let schema_map: &Map<String, Value> = match schema {
+            Value::Bool(true)    => return,
+            Value::Bool(false)   => {
+                                        push_error(errors, instance_path, schema_path, "false schema does not allow any value".to_string());
+                                        return;
+                                    },
+            Value::Object(obj)   => obj,
+            _                    => {
+                                        push_error(errors, instance_path, schema_path, "schema is not an object or boolean".to_string());
+                                        return;
+                                    }
+        };
+        let scope_uri: String = match schema_id(schema_map, self.draft) {
+            Some(id) => resolve_uri(base_uri, &id),
+            None     => base_uri.to_string(),
+        };
+        if let Some(Value::String(reference)) = schema_map.get("$ref") {
+            let ref_path = format!("{}/$ref", schema_path);
+            let target_uri = resolve_uri(&scope_uri, reference);
+            match self.resolve_ref(&target_uri) {
+                Some(target) => self.validate_node(target, instance, strip_fragment(&target_uri), instance_path, &ref_path, errors),
+                None         => push_error(errors, instance_path, &ref_path, format!("could not resolve $ref {}", reference)),
+            }
+            if self.draft.ref_overrides_siblings() {
+                return;
+            }
+        }
+        for (keyword, keyword_value) in schema_map {
+            if !self.is_keyword(keyword) {
+                continue;
+            }
+            let keyword_path = format!("{}/{}", schema_path, escape_pointer_token(keyword));
+            self.validate_keyword(keyword, keyword_value, schema_map, instance, &scope_uri, instance_path, &keyword_path, errors);
+        }
+    }
+
+    /// apply a single keyword to an instance
+    #[allow(clippy::too_many_arguments)]
+    fn validate_keyword(&self, keyword: &str, keyword_value: &'a Value, schema_map: &'a Map<String, Value>, instance: &Value, base_uri: &str, instance_path: &str, keyword_path: &str, errors: &mut Vec<ValidationError>) {
+        match keyword {
+            "type"                 => {
+                                          let allowed: Vec<&str> = match keyword_value {
+                                              Value::String(type_name) => vec![type_name.as_str()],
+                                              Value::Array(type_names) => type_names.iter().filter_map(|t| t.as_str()).collect(),
+                                              _                        => vec![],
+                                          };
+                                          if !allowed.iter().any(|type_name| instance_is_type(instance, type_name, self.draft)) {
+                                              push_error(errors, instance_path, keyword_path, format!("{} is not of type {}", instance, keyword_value));
+                                          }
+                                      },
+            "enum"                 => {
+                                          if let Value::Array(options) = keyword_value {
+                                              if !options.iter().any(|option| json_equal(option, instance)) {
+                                                  push_error(errors, instance_path, keyword_path, format!("{} is not one of {}", instance, keyword_value));
+                                              }
+                                          }
+                                      },
+            "const" if !json_equal(keyword_value, instance) => {
+                                          push_error(errors, instance_path, keyword_path, format!("{} is not {}", instance, keyword_value));
+                                      },
+            "multipleOf"           => {
+                                          if let (Some(value), Some(divisor)) = (instance.as_f64(), keyword_value.as_f64()) {
+                                              let quotient = value / divisor;
+                                              if quotient.is_finite() && (quotient - quotient.round()).abs() > 1e-9 {
+                                                  push_error(errors, instance_path, keyword_path, format!("{} is not a multiple of {}", instance, keyword_value));
+                                              }
+                                          }
+                                      },
+            "maximum"              => {
+                                          let exclusive = self.draft.has_boolean_exclusive_bounds() && schema_map.get("exclusiveMaximum") == Some(&Value::Bool(true));
+                                          if let (Some(value), Some(limit)) = (instance.as_f64(), keyword_value.as_f64()) {
+                                              if value > limit || (exclusive && value == limit) {
+                                                  push_error(errors, instance_path, keyword_path, format!("{} is greater than the maximum of {}", instance, keyword_value));
+                                              }
+                                          }
+                                      },
+            "minimum"              => {
+                                          let exclusive = self.draft.has_boolean_exclusive_bounds() && schema_map.get("exclusiveMinimum") == Some(&Value::Bool(true));
+                                          if let (Some(value), Some(limit)) = (instance.as_f64(), keyword_value.as_f64()) {
+                                              if value < limit || (exclusive && value == limit) {
+                                                  push_error(errors, instance_path, keyword_path, format!("{} is less than the minimum of {}", instance, keyword_value));
+                                              }
+                                          }
+                                      },
+            "exclusiveMaximum"     => {
+                                          if let (Some(value), Some(limit)) = (instance.as_f64(), keyword_value.as_f64()) {
+                                              if value >= limit {
+                                                  push_error(errors, instance_path, keyword_path, format!("{} is not less than {}", instance, keyword_value));
+                                              }
+                                          }
+                                      },
+            "exclusiveMinimum"     => {
+                                          if let (Some(value), Some(limit)) = (instance.as_f64(), keyword_value.as_f64()) {
+                                              if value <= limit {
+                                                  push_error(errors, instance_path, keyword_path, format!("{} is not greater than {}", instance, keyword_value));
+                                              }
+                                          }
+                                      },
+            "maxLength"            => {
+                                          if let (Value::String(text), Some(limit)) = (instance, keyword_value.as_u64()) {
+                                              if text.chars().count() as u64 > limit {
+                                                  push_error(errors, instance_path, keyword_path, format!("{} is longer than {} characters", instance, limit));
+                                              }
+                                          }
+                                      },
+            "minLength"            => {
+                                          if let (Value::String(text), Some(limit)) = (instance, keyword_value.as_u64()) {
+                                              if (text.chars().count() as u64) < limit {
+                                                  push_error(errors, instance_path, keyword_path, format!("{} is shorter than {} characters", instance, limit));
+                                              }
+                                          }
+                                      },
+            "pattern"              => {
+                                          if let (Value::String(text), Value::String(pattern)) = (instance, keyword_value) {
+                                              match Regex::new(pattern) {
+                                                  Ok(regex) => if !regex.is_match(text) {
+                                                                   push_error(errors, instance_path, keyword_path, format!("{} does not match {}", instance, pattern));
+                                                               },
+                                                  Err(_)    => push_error(errors, instance_path, keyword_path, format!("could not compile pattern {}", pattern)),
+                                              }
+                                          }
+                                      },
+            "prefixItems"          => {
+                                          if let (Value::Array(elements), Value::Array(item_schemas)) = (instance, keyword_value) {
+                                              for (position, (element, item_schema)) in elements.iter().zip(item_schemas.iter()).enumerate() {
+                                                  self.validate_node(item_schema, element, base_uri, &format!("{}/{}", instance_path, position), &format!("{}/{}", keyword_path, position), errors);
+                                              }
+                                          }
+                                      },
+            "items"                => {
+                                          if let Value::Array(elements) = instance {
+                                              let prefix_length = match schema_map.get("prefixItems") {
+                                                  Some(Value::Array(item_schemas)) if self.draft.has_prefix_items() => item_schemas.len(),
+                                                  _                                                                 => 0,
+                                              };
+                                              match keyword_value {
+                                                  Value::Array(item_schemas) if !self.draft.has_prefix_items() => {
+                                                      for (position, (element, item_schema)) in elements.iter().zip(item_schemas.iter()).enumerate() {
+                                                          self.validate_node(item_schema, element, base_uri, &format!("{}/{}", instance_path, position), &format!("{}/{}", keyword_path, position), errors);
+                                                      }
+                                                  },
+                                                  _                          => {
+                                                      for (position, element) in elements.iter().enumerate().skip(prefix_length) {
+                                                          self.validate_node(keyword_value, element, base_uri, &format!("{}/{}", instance_path, position), keyword_path, errors);
+                                                      }
+                                                  }
+                                              }
+                                          }
+                                      },
+            "additionalItems"      => {
+                                          if let (Value::Array(elements), Some(Value::Array(item_schemas))) = (instance, schema_map.get("items")) {
+                                              for (position, element) in elements.iter().enumerate().skip(item_schemas.len()) {
+                                                  self.validate_node(keyword_value, element, base_uri, &format!("{}/{}", instance_path, position), keyword_path, errors);
+                                              }
+                                          }
+                                      },
+            "contains"             => {
+                                          if let Value::Array(elements) = instance {
+                                              if !elements.iter().any(|element| self.is_valid(keyword_value, element, base_uri)) {
+                                                  push_error(errors, instance_path, keyword_path, "no element matches the contains schema".to_string());
+                                              }
+                                          }
+                                      },
+            "maxItems"             => {
+                                          if let (Value::Array(elements), Some(limit)) = (instance, keyword_value.as_u64()) {
+                                              if elements.len() as u64 > limit {
+                                                  push_error(errors, instance_path, keyword_path, format!("array has more than {} items", limit));
+                                              }
+                                          }
+                                      },
+            "minItems"             => {
+                                          if let (Value::Array(elements), Some(limit)) = (instance, keyword_value.as_u64()) {
+                                              if (elements.len() as u64) < limit {
+                                                  push_error(errors, instance_path, keyword_path, format!("array has fewer than {} items", limit));
+                                              }
+                                          }
+                                      },
+            "uniqueItems"          => {
+                                          if let (Value::Array(elements), Value::Bool(true)) = (instance, keyword_value) {
+                                              let has_duplicates = elements.iter().enumerate()
+                                                  .any(|(position, element)| elements[position + 1..].iter().any(|other| json_equal(element, other)));
+                                              if has_duplicates {
+                                                  push_error(errors, instance_path, keyword_path, "array items are not unique".to_string());
+                                              }
+                                          }
+                                      },
+            "maxProperties"        => {
+                                          if let (Value::Object(members), Some(limit)) = (instance, keyword_value.as_u64()) {
+                                              if members.len() as u64 > limit {
+                                                  push_error(errors, instance_path, keyword_path, format!("object has more than {} properties", limit));
+                                              }
+                                          }
+                                      },
+            "minProperties"        => {
+                                          if let (Value::Object(members), Some(limit)) = (instance, keyword_value.as_u64()) {
+                                              if (members.len() as u64) < limit {
+                                                  push_error(errors, instance_path, keyword_path, format!("object has fewer than {} properties", limit));
+                                              }
+                                          }
+                                      },
+            "required"             => {
+                                          if let (Value::Object(members), Value::Array(required_names)) = (instance, keyword_value) {
+                                              for required_name in required_names.iter().filter_map(|n| n.as_str()) {
+                                                  if !members.contains_key(required_name) {
+                                                      push_error(errors, instance_path, keyword_path, format!("missing required property {}", required_name));
+                                                  }
+                                              }
+                                          }
+                                      },
+            "properties"           => {
+                                          if let (Value::Object(members), Value::Object(property_schemas)) = (instance, keyword_value) {
+                                              for (member_name, member_value) in members {
+                                                  if let Some(property_schema) = property_schemas.get(member_name) {
+                                                      let member_token = escape_pointer_token(member_name);
+                                                      self.validate_node(property_schema, member_value, base_uri, &format!("{}/{}", instance_path, member_token), &format!("{}/{}", keyword_path, member_token), errors);
+                                                  }
+                                              }
+                                          }
+                                      },
+            "patternProperties"    => {
+                                          if let (Value::Object(members), Value::Object(pattern_schemas)) = (instance, keyword_value) {
+                                              for (pattern, pattern_schema) in pattern_schemas {
+                                                  let regex = match Regex::new(pattern) {
+                                                      Ok(regex) => regex,
+                                                      Err(_)    => {
+                                                                       push_error(errors, instance_path, keyword_path, format!("could not compile pattern {}", pattern));
+                                                                       continue;
+                                                                   }
+                                                  };
+                                                  for (member_name, member_value) in members.iter().filter(|(name, _)| regex.is_match(name)) {
+                                                      self.validate_node(pattern_schema, member_value, base_uri, &format!("{}/{}", instance_path, escape_pointer_token(member_name)), &format!("{}/{}", keyword_path, escape_pointer_token(pattern)), errors);
+                                                  }
+                                              }
+                                          }
+                                      },
+            "additionalProperties" => {
+                                          if let Value::Object(members) = instance {
+                                              let patterns: Vec<Regex> = match schema_map.get("patternProperties") {
+                                                  Some(Value::Object(pattern_schemas)) => pattern_schemas.keys().filter_map(|p| Regex::new(p).ok()).collect(),
+                                                  _                                    => vec![],
+                                              };
+                                              for (member_name, member_value) in members {
+                                                  let declared = matches!(schema_map.get("properties"), Some(Value::Object(property_schemas)) if property_schemas.contains_key(member_name));
+                                                  if declared || patterns.iter().any(|regex| regex.is_match(member_name)) {
+                                                      continue;
+                                                  }
+                                                  self.validate_node(keyword_value, member_value, base_uri, &format!("{}/{}", instance_path, escape_pointer_token(member_name)), keyword_path, errors);
+                                              }
+                                          }
+                                      },
+            "dependencies"         => {
+                                          if let (Value::Object(members), Value::Object(dependencies)) = (instance, keyword_value) {
+                                              for (trigger_name, dependency) in dependencies.iter().filter(|(name, _)| members.contains_key(name.as_str())) {
+                                                  let dependency_path = format!("{}/{}", keyword_path, escape_pointer_token(trigger_name));
+                                                  if let Value::Array(required_names) = dependency {
+                                                      for required_name in required_names.iter().filter_map(|n| n.as_str()) {
+                                                          if !members.contains_key(required_name) {
+                                                              push_error(errors, instance_path, &dependency_path, format!("{} requires property {}", trigger_name, required_name));
+                                                          }
+                                                      }
+                                                  } else {
+                                                      self.validate_node(dependency, instance, base_uri, instance_path, &dependency_path, errors);
+                                                  }
+                                              }
+                                          }
+                                      },
+            "dependentRequired"    => {
+                                          if let (Value::Object(members), Value::Object(dependencies)) = (instance, keyword_value) {
+                                              for (trigger_name, required_names) in dependencies.iter().filter(|(name, _)| members.contains_key(name.as_str())) {
+                                                  let dependency_path = format!("{}/{}", keyword_path, escape_pointer_token(trigger_name));
+                                                  for required_name in required_names.as_array().into_iter().flatten().filter_map(|n| n.as_str()) {
+                                                      if !members.contains_key(required_name) {
+                                                          push_error(errors, instance_path, &dependency_path, format!("{} requires property {}", trigger_name, required_name));
+                                                      }
+                                                  }
+                                              }
+                                          }
+                                      },
+            "dependentSchemas"     => {
+                                          if let (Value::Object(members), Value::Object(dependencies)) = (instance, keyword_value) {
+                                              for (trigger_name, dependency) in dependencies.iter().filter(|(name, _)| members.contains_key(name.as_str())) {
+                                                  self.validate_node(dependency, instance, base_uri, instance_path, &format!("{}/{}", keyword_path, escape_pointer_token(trigger_name)), errors);
+                                              }
+                                          }
+                                      },
+            "if"                   => {
+                                          // then and else only apply through if, and mean nothing on their own
+                                          let chosen_keyword: &str = if self.is_valid(keyword_value, instance, base_uri) { "then" } else { "else" };
+                                          if let Some(chosen_schema) = schema_map.get(chosen_keyword) {
+                                              let chosen_path: String = format!("{}{}", keyword_path.strip_suffix("if").unwrap_or(keyword_path), chosen_keyword);
+                                              self.validate_node(chosen_schema, instance, base_uri, instance_path, &chosen_path, errors);
+                                          }
+                                      },
+            "propertyNames"        => {
+                                          if let Value::Object(members) = instance {
+                                              for member_name in members.keys() {
+                                                  let name_value = Value::String(member_name.to_string());
+                                                  self.validate_node(keyword_value, &name_value, base_uri, &format!("{}/{}", instance_path, escape_pointer_token(member_name)), keyword_path, errors);
+                                              }
+                                          }
+                                      },
+            "allOf"                => {
+                                          if let Value::Array(subschemas) = keyword_value {
+                                              for (position, subschema) in subschemas.iter().enumerate() {
+                                                  self.validate_node(subschema, instance, base_uri, instance_path, &format!("{}/{}", keyword_path, position), errors);
+                                              }
+                                          }
+                                      },
+            "anyOf"                => {
+                                          if let Value::Array(subschemas) = keyword_value {
+                                              if !subschemas.iter().any(|subschema| self.is_valid(subschema, instance, base_uri)) {
+                                                  push_error(errors, instance_path, keyword_path, format!("{} is not valid under any of the given schemas", instance));
+                                              }
+                                          }
+                                      },
+            "oneOf"                => {
+                                          if let Value::Array(subschemas) = keyword_value {
+                                              let matching = subschemas.iter().filter(|subschema| self.is_valid(subschema, instance, base_uri)).count();
+                                              if matching != 1 {
+                                                  push_error(errors, instance_path, keyword_path, format!("{} is valid under {} of the given schemas, expected exactly one", instance, matching));
+                                              }
+                                          }
+                                      },
+            "not" if self.is_valid(keyword_value, instance, base_uri) => {
+                                          push_error(errors, instance_path, keyword_path, format!("{} should not be valid under {}", instance, keyword_value));
+                                      },
+            _                      => {
+                                          // annotations and unsupported keywords are ignored
+                                      }
+        }
+    }
+
+    /// whether a keyword has a meaning in the draft in force
+    fn is_keyword(&self, keyword: &str) -> bool {
+        match keyword {
+            "exclusiveMaximum" | "exclusiveMinimum"   => !self.draft.has_boolean_exclusive_bounds(),
+            "const" | "contains" | "propertyNames"    => self.draft.has_draft6_keywords(),
+            "additionalItems"                         => !self.draft.has_prefix_items(),
+            "prefixItems"                             => self.draft.has_prefix_items(),
+            "dependencies"                            => !self.draft.has_split_dependencies(),
+            "dependentRequired" | "dependentSchemas"  => self.draft.has_split_dependencies(),
+            "if"                                      => self.draft.has_conditionals(),
+            _                                         => true,
+        }
+    }
+
+    /// find the subschema an absolute URI (with optional fragment) points at
+    fn resolve_ref(&self, target_uri: &str) -> Option<&'a Value> {
+        if let Some(&anchored) = self.index.get(target_uri) {
+            return Some(anchored);
+        }
+        let (document_uri, fragment) = match target_uri.find('#') {
+            Some(position) => (&target_uri[..position], &target_uri[position + 1..]),
+            None           => (target_uri, ""),
+        };
+        let document: &'a Value = self.index.get(document_uri)?;
+        resolve_pointer(document, &percent_decode(fragment))
+    }
+}
+
+/// add an error to the list
+fn push_error(errors: &mut Vec<ValidationError>, instance_path: &str, schema_path: &str, message: String) {
+    errors.push(ValidationError {
+        instance_path: instance_path.to_string(),
+        schema_path: schema_path.to_string(),
+        message,
+    });
+}
+
+/// the id a schema declares for itself, if any, with a 2019-09 $anchor expressed as a fragment
+fn schema_id(schema_map: &Map<String, Value>, draft: Draft) -> Option<String> {
+    if draft.ref_overrides_siblings() && schema_map.contains_key("$ref") {
+        return None;
+    }
+    match (schema_map.get(draft.id_keyword()), schema_map.get("$anchor")) {
+        (Some(Value::String(id)), _)                                     => Some(id.to_string()),
+        (_, Some(Value::String(anchor))) if draft >= Draft::Draft201909 => Some(format!("#{}", anchor)),
+        _                                                                => None,
+    }
+}
+
+/// record a document, and every subschema in it that declares an id, under its absolute URI
+fn index_schema_ids<'a>(document: &'a Value, document_uri: &str, draft: Draft, index: &mut HashMap<String, &'a Value>) {
+    index.entry(document_uri.to_string()).or_insert(document);
+    index_subschema_ids(document, document_uri, draft, index);
+}
+
+/// walk a schema recording subschemas with ids
+fn index_subschema_ids<'a>(value: &'a Value, base_uri: &str, draft: Draft, index: &mut HashMap<String, &'a Value>) {
+    match value {
+        Value::Object(value_map) => {
+            let mut scope_uri: String = base_uri.to_string();
+            if let Some(id) = schema_id(value_map, draft) {
+                scope_uri = resolve_uri(base_uri, &id);
+                index.insert(scope_uri.clone(), value);
+            }
+            for (keyword, child) in value_map {
+                if !matches!(keyword.as_str(), "enum" | "const" | "default" | "examples") {
+                    index_subschema_ids(child, &scope_uri, draft, index);
+                }
+            }
+        },
+        Value::Array(elements)   => {
+            for child in elements {
+                index_subschema_ids(child, base_uri, draft, index);
+            }
+        },
+        _                        => {}
+    }
+}
+
+/// follow a JSON Pointer into a document
+pub fn resolve_pointer<'a>(document: &'a Value, pointer: &str) -> Option<&'a Value> {
+    if pointer.is_empty() {
+        return Some(document);
+    }
+    if !pointer.starts_with('/') {
+        return None;
+    }
+    let mut current: &'a Value = document;
+    for token in pointer[1..].split('/') {
+        let token = token.replace("~1", "/").replace("~0", "~");
+        current = match current {
+            Value::Object(current_map) => current_map.get(&token)?,
+            Value::Array(elements)     => elements.get(token.parse::<usize>().ok()?)?,
+            _                          => return None,
+        };
+    }
+    Some(current)
+}
+
+/// escape a single JSON Pointer reference token
+pub fn escape_pointer_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+/// decode %XX escapes in a URI fragment
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded: Vec<u8> = Vec::with_capacity(bytes.len());
+    let mut position = 0;
+    while position < bytes.len() {
+        if bytes[position] == b'%' && position + 2 < bytes.len() {
+            if let Ok(byte) = u8::from_str_radix(&text[position + 1..position + 3], 16) {
+                decoded.push(byte);
+                position += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[position]);
+        position += 1;
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// remove the fragment from a URI
+fn strip_fragment(uri: &str) -> &str {
+    match uri.find('#') {
+        Some(position) => &uri[..position],
+        None           => uri,
+    }
+}
+
+/// resolve a URI reference against a base URI
+pub fn resolve_uri(base_uri: &str, reference: &str) -> String {
+    let has_scheme = reference.find(':').map(|colon| {
+        let scheme = &reference[..colon];
+        !scheme.is_empty() && scheme.chars().all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '-' || c == '.')
+    }).unwrap_or(false);
+    if has_scheme {
+        return reference.to_string();
+    }
+    let base_document = strip_fragment(base_uri);
+    if reference.starts_with('#') || reference.is_empty() {
+        return format!("{}{}", base_document, reference);
+    }
+    let scheme_end = base_document.find("://").map(|position| position + 3).unwrap_or(0);
+    if let Some(network_path) = reference.strip_prefix("//") {
+        return format!("{}{}", &base_document[..scheme_end], network_path);
+    }
+    let authority_end = base_document[scheme_end..].find('/').map(|position| position + scheme_end).unwrap_or(base_document.len());
+    let merged_path: String = if reference.starts_with('/') {
+        reference.to_string()
+    } else {
+        let base_path = &base_document[authority_end..];
+        let directory = match base_path.rfind('/') {
+            Some(position) => &base_path[..position + 1],
+            None           => "/",
+        };
+        format!("{}{}", directory, reference)
+    };
+    format!("{}{}", &base_document[..authority_end], remove_dot_segments(&merged_path))
+}
+
+/// collapse "." and ".." path segments
+fn remove_dot_segments(path: &str) -> String {
+    let (path_only, suffix) = match path.find('#') {
+        Some(position) => (&path[..position], &path[position..]),
+        None           => (path, ""),
+    };
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in path_only.split('/') {
+        match segment {
+            "."  => {},
+            ".." => { segments.pop(); },
+            _    => segments.push(segment),
+        }
+    }
+    let mut rslt = segments.join("/");
+    if !rslt.starts_with('/') && path_only.starts_with('/') {
+        rslt = format!("/{}", rslt);
+    }
+    format!("{}{}", rslt, suffix)
+}
+
+/// check an instance against a single JSON Schema type name
+fn instance_is_type(instance: &Value, type_name: &str, draft: Draft) -> bool {
+    match type_name {
+        "null"    => instance.is_null(),
+        "boolean" => instance.is_boolean(),
+        "object"  => instance.is_object(),
+        "array"   => instance.is_array(),
+        "string"  => instance.is_string(),
+        "number"  => instance.is_number(),
+        "integer" => instance.is_i64() || instance.is_u64() || instance.as_f64().map(|f| is_integral_float(f, draft)).unwrap_or(false),
+        _         => false,
+    }
+}
+
+/// whether a number parsed as a float counts as an integer
+fn is_integral_float(value: f64, draft: Draft) -> bool {
+    if value.fract() != 0.0 {
+        return false;
+    }
+    // integers that fit 64 bits are parsed as integers, so a float in that range was written with
+    // a fraction or exponent; only draft 6 and later accept those as integers
+    draft.integer_allows_zero_fraction() || value.abs() >= u64::MAX as f64
+}
+
+/// JSON equality, where numbers compare by value
+pub fn json_equal(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Number(left_number), Value::Number(right_number)) => {
+            if let (Some(l), Some(r)) = (left_number.as_i64(), right_number.as_i64()) {
+                return l == r;
+            }
+            if let (Some(l), Some(r)) = (left_number.as_u64(), right_number.as_u64()) {
+                return l == r;
+            }
+            left_number.as_f64() == right_number.as_f64()
+        },
+        (Value::Array(left_elements), Value::Array(right_elements)) => {
+            left_elements.len() == right_elements.len()
+                && left_elements.iter().zip(right_elements.iter()).all(|(l, r)| json_equal(l, r))
+        },
+        (Value::Object(left_map), Value::Object(right_map)) => {
+            left_map.len() == right_map.len()
+                && left_map.iter().all(|(key, l)| right_map.get(key).map(|r| json_equal(l, r)).unwrap_or(false))
+        },
+        _ => left == right,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// validate with an explicit draft
+    fn is_valid_in(draft: Draft, schema: &str, instance: &str) -> bool {
+        let schema_json: Value = serde_json::from_str(schema).unwrap();
+        let instance_json: Value = serde_json::from_str(instance).unwrap();
+        validate_with_remotes(&schema_json, &instance_json, &HashMap::new(), Some(draft)).is_ok()
+    }
+
+    #[test]
+    fn validate_exclusive_bounds_per_draft() {
+        let boolean_form = r#"{ "maximum": 3, "exclusiveMaximum": true }"#;
+        assert!(!is_valid_in(Draft::Draft4, boolean_form, "3"));
+        assert!(is_valid_in(Draft::Draft202012, boolean_form, "3"));
+
+        let numeric_form = r#"{ "exclusiveMaximum": 3 }"#;
+        assert!(is_valid_in(Draft::Draft4, numeric_form, "3"));
+        assert!(!is_valid_in(Draft::Draft7, numeric_form, "3"));
+    }
+
+    #[test]
+    fn validate_positional_items_per_draft() {
+        let array_items = r#"{ "items": [{ "type": "integer" }], "additionalItems": false }"#;
+        assert!(is_valid_in(Draft::Draft7, array_items, "[1]"));
+        assert!(!is_valid_in(Draft::Draft7, array_items, "[1, 2]"));
+        assert!(!is_valid_in(Draft::Draft7, array_items, r#"["a"]"#));
+
+        let prefix_items = r#"{ "prefixItems": [{ "type": "integer" }], "items": { "type": "string" } }"#;
+        assert!(is_valid_in(Draft::Draft202012, prefix_items, r#"[1, "a", "b"]"#));
+        assert!(!is_valid_in(Draft::Draft202012, prefix_items, r#"[1, 2]"#));
+        assert!(!is_valid_in(Draft::Draft202012, prefix_items, r#"["a"]"#));
+    }
+
+    #[test]
+    fn validate_dependent_keywords_per_draft() {
+        let dependent_required = r#"{ "dependentRequired": { "credit_card": ["billing_address"] } }"#;
+        assert!(is_valid_in(Draft::Draft202012, dependent_required, r#"{ "name": "ada" }"#));
+        assert!(!is_valid_in(Draft::Draft202012, dependent_required, r#"{ "credit_card": "1234" }"#));
+        assert!(is_valid_in(Draft::Draft7, dependent_required, r#"{ "credit_card": "1234" }"#));
+
+        let dependent_schemas = r#"{ "dependentSchemas": { "credit_card": { "required": ["billing_address"], "properties": { "billing_address": { "type": "string" } } } } }"#;
+        assert!(is_valid_in(Draft::Draft202012, dependent_schemas, r#"{ "credit_card": "1234", "billing_address": "Dam 1" }"#));
+        assert!(!is_valid_in(Draft::Draft202012, dependent_schemas, r#"{ "credit_card": "1234", "billing_address": 1 }"#));
+        assert!(!is_valid_in(Draft::Draft201909, dependent_schemas, r#"{ "credit_card": "1234" }"#));
+    }
+
+    #[test]
+    fn validate_if_then_else_per_draft() {
+        let schema = r#"{
+            "if": { "properties": { "country": { "const": "NL" } } },
+            "then": { "properties": { "postal_code": { "pattern": "^[0-9]{4} ?[A-Z]{2}$" } } },
+            "else": { "properties": { "postal_code": { "pattern": "^[0-9]{5}$" } } }
+        }"#;
+        assert!(is_valid_in(Draft::Draft7, schema, r#"{ "country": "NL", "postal_code": "1012 AB" }"#));
+        assert!(!is_valid_in(Draft::Draft7, schema, r#"{ "country": "NL", "postal_code": "20500" }"#));
+        assert!(is_valid_in(Draft::Draft202012, schema, r#"{ "country": "US", "postal_code": "20500" }"#));
+        assert!(!is_valid_in(Draft::Draft202012, schema, r#"{ "country": "US", "postal_code": "1012 AB" }"#));
+        assert!(is_valid_in(Draft::Draft6, schema, r#"{ "country": "US", "postal_code": "1012 AB" }"#));
+        let errors = validate_with_remotes(&serde_json::from_str(schema).unwrap(), &serde_json::json!({ "country": "US", "postal_code": "x" }), &HashMap::new(), Some(Draft::Draft7)).unwrap_err();
+        assert_eq!(errors[0].schema_path, "/else/properties/postal_code/pattern");
+    }
+
+    #[test]
+    fn validate_ref_siblings_per_draft() {
+        let schema = r##"{ "$defs": { "small": { "maximum": 10 } }, "$ref": "#/$defs/small", "minimum": 5 }"##;
+        assert!(is_valid_in(Draft::Draft7, schema, "1"));
+        assert!(!is_valid_in(Draft::Draft202012, schema, "1"));
+        assert!(!is_valid_in(Draft::Draft7, schema, "11"));
+    }
+
+    #[test]
+    fn validate_integer_zero_fraction_per_draft() {
+        assert!(!is_valid_in(Draft::Draft4, r#"{ "type": "integer" }"#, "1.0"));
+        assert!(is_valid_in(Draft::Draft6, r#"{ "type": "integer" }"#, "1.0"));
+    }
+
+    #[test]
+    fn validate_detects_draft_from_schema() {
+        let schema: Value = serde_json::from_str(r#"{ "$schema": "http://json-schema.org/draft-04/schema#", "maximum": 3, "exclusiveMaximum": true }"#).unwrap();
+        let errors = validate(&schema, &Value::from(3)).unwrap_err();
+        assert_eq!(errors[0].schema_path, "/maximum");
+    }
+}
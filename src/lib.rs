@@ -1,15 +1,13 @@
-/// Copyright (c) 2023  Ward van der Veer
+//! Copyright (c) 2023  Ward van der Veer
 
 extern crate proc_macro;
-extern crate serde_json;
-use proc_macro::TokenStream;
+extern crate json_schema_parser_core;
+use proc_macro::{Delimiter, Group, Literal, Spacing, TokenStream, TokenTree};
 use std::str::FromStr;
-use std::collections::HashMap;
 
-mod processor;
-
-use processor::json_schema_to_struct;
-use processor::json_schema_file_impl;
+use json_schema_parser_core::{json_schema_to_struct, json_schema_dir_impl, json_schema_file_impl, json_schema_file_pointer_impl, jtd_file_impl, openapi_file_impl};
+use json_schema_parser_core::{Collection, GeneratorOptions, SourceFormat};
+use json_schema_parser_core::Draft;
 
 
 /// include JSON Schema directly in the Rust code
@@ -19,12 +17,10 @@ use processor::json_schema_file_impl;
 #[proc_macro]
 pub fn json_schema_here(schema_body: TokenStream) -> TokenStream {
     let schema_text: String = schema_body.to_string(); 
-    let custom_name_map: HashMap<String, String> = HashMap::new();
-    let custom_type_map: HashMap<String, String> = HashMap::new();
-    let struct_text = json_schema_to_struct(&schema_text, &custom_name_map, &custom_type_map);
+    let struct_text = json_schema_to_struct(&schema_text, &GeneratorOptions::default());
     let ts  = TokenStream::from_str(&struct_text.unwrap());
     match ts {
-        Ok(rslt)      => return rslt,
+        Ok(rslt)      => rslt,
         Err(err_msg)     => panic!("Could not parse error {} from JSON Schema {}\n", err_msg, schema_text)
     }
 }
@@ -34,47 +30,524 @@ pub fn json_schema_here(schema_body: TokenStream) -> TokenStream {
 /// 
 /// json_schema_file("<filename>", "<custom_type1>", "<custom_name1>", "<custom_name2>", "<custom_type2>", ...);
 /// 
-/// <filename> is relative path, e.g. "src/schema.json"
+/// the options can also be given as Rust, e.g.
+/// json_schema_file(path = "<filename>", rename { "my field" => my_field }, types { flag: bool }, derive(Debug), json_schema = true);
+/// 
+/// <filename> is relative path to the Cargo.toml of the crate, e.g. "src/schema.json", or to the build script output
+/// directory with "$OUT_DIR/", e.g. "$OUT_DIR/schema.json"
 /// 
 /// <custom_type> is "name=type", e.g. "flag=bool"
 /// 
 /// <custom_name is "old_name->new_name", e.g. "my field->my_field"
 /// 
+/// a name or type can be given for one property by JSON Pointer or dotted path, with * and ** globs,
+/// e.g. "/properties/info/properties/seed=i64", "info.seed->random_seed" or "results[].*.amount=i64";
+/// a bare name applies to the property of that name anywhere
+/// 
+/// <filename> may be JSON, YAML (.yaml, .yml) or JSON5 (.json5), chosen by extension or with "$format=<format>", e.g. "$format=yaml"
+/// 
+/// the draft is taken from "$schema", or can be overridden with "$schema=<draft>", e.g. "$schema=draft-04"
+/// 
+/// arrays are Vec unless "$uniqueItems=<BTreeSet|HashSet>", "$fixedItems=Array" or "$minItems=NonEmpty" is given,
+/// or a field chooses its own with "<field>[]=<collection>", e.g. "tags[]=BTreeSet"
+/// 
+/// "$jsonSchema=true" gives every type a json_schema() function returning the schema it was generated from,
+/// with the custom names and types applied
+/// 
+/// "$derive=<derives>" adds derives to every type, e.g. "$derive=Debug, PartialEq", and "$attr=<attribute>" adds an
+/// attribute, e.g. "$attr=#[serde(deny_unknown_fields)]", or "<type>$derive=..." and "<type>$attr=..." to one type;
+/// a derive is left out of types with a field that can't have it, e.g. Eq with an f64 field
+/// 
+/// generated code reaches serde and serde_json as ::serde and ::serde_json, or by the paths given with
+/// "$serde=<path>" and "$serdeJson=<path>", e.g. "$serde=my_crate::serde" for a re-exported serde
+/// 
+/// "$builders=true" gives every struct a builder, e.g. User::builder().email("a@example.com").build(), which fails
+/// when a required property is not set and fills in schema defaults
+/// 
+/// an object without properties becomes a serde_json::Map<String, Value>, or the type given with
+/// "$freeFormObject=<BTreeMap|HashMap|type>", and a schema without a type becomes a serde_json::Value
+/// 
+/// a $def that is a string or number with constraints, e.g. minLength or minimum, becomes a newtype made with
+/// TryFrom, that fails for values not meeting them, as does deserializing; patterns are checked too when
+/// "$regex=<path>" gives the path to the regex crate, e.g. "$regex=::regex"
+/// 
+/// "$roots=<pointers>" generates only the schemas at those JSON Pointers and the types they refer to,
+/// e.g. "$roots=/definitions/cell" or roots("/definitions/cell", "/definitions/output"), "" being the root schema
+/// 
+/// "$module=<name>" puts the generated types in a module of that name, whose visibility is "pub" unless given with
+/// "$visibility=<pub|pub(crate)|private>"; without a module, "$visibility" sets the visibility of the types
+/// 
 #[proc_macro]
 pub fn json_schema_file(parameters: TokenStream) -> TokenStream {
-    let mut parameter_number: i32 = 1;
-    let mut file_path: String = "".to_string();
-    let mut custom_name_map: HashMap<String, String> = HashMap::new();
-    let mut custom_type_map: HashMap<String, String> = HashMap::new();
-    for parameter in parameters.into_iter() {
-        let syntax: String = parameter.to_string();
-        if &syntax[0..1] != "\"" || &syntax[syntax.len()-1..syntax.len()] != "\"" {
-            continue;
+    let (file_path, _, options) = parse_file_parameters(parameters, None);
+    let struct_text = json_schema_file_impl(file_path.clone(), &options);
+    let ts  = TokenStream::from_str(&struct_text);
+    match ts {
+        Ok(rslt)      => rslt,
+        Err(err_msg)     => panic!("Could not parse error {} from JSON Schema in {}\n", err_msg, file_path)
+    }
+}
+
+/// include every JSON Schema file in a directory, or those matching a pattern, as a module per file,
+/// supports the same options as json_schema_file
+///
+/// json_schema_dir("<directory>", "<pattern>", ...);
+///
+/// e.g. json_schema_dir!("schemas", "*.json") gives a module user for "schemas/user.json", and a $ref to
+/// "address.json" or "address.json#/$defs/street" in it refers to the type in the module address
+///
+#[proc_macro]
+pub fn json_schema_dir(parameters: TokenStream) -> TokenStream {
+    let (dir_path, file_pattern, options) = parse_file_parameters(parameters, Some("pattern"));
+    let struct_text = json_schema_dir_impl(dir_path.clone(), file_pattern.as_deref().unwrap_or("*.json"), &options);
+    let ts  = TokenStream::from_str(&struct_text);
+    match ts {
+        Ok(rslt)      => rslt,
+        Err(err_msg)     => panic!("Could not parse error {} from JSON Schema in {}\n", err_msg, dir_path)
+    }
+}
+
+/// fill a module with the types for a JSON Schema file, after the items already in it, or generate one definition
+/// of the file as the annotated struct, supports the same options as json_schema_file
+///
+/// #[json_schema(file = "<filename>", ...)] mod model { ... }
+///
+/// #[json_schema(file = "<filename>", pointer = "/$defs/User", ...)] pub struct User;
+///
+/// the struct's derives and attributes are added to the generated type, and $refs in the definition name the types
+/// of the other definitions, which are left to be generated elsewhere
+///
+#[proc_macro_attribute]
+pub fn json_schema(parameters: TokenStream, item: TokenStream) -> TokenStream {
+    let (file_path, pointer, mut options) = parse_file_parameters(parameters, Some("pointer"));
+    let item_tokens: Vec<TokenTree> = item.into_iter().collect();
+    // outer attributes, then the visibility, then mod or struct
+    let mut position: usize = 0;
+    let mut attributes: Vec<Vec<TokenTree>> = Vec::new();
+    while let (Some(TokenTree::Punct(hash)), Some(TokenTree::Group(attribute))) = (item_tokens.get(position), item_tokens.get(position + 1)) {
+        if hash.as_char() != '#' || attribute.delimiter() != Delimiter::Bracket {
+            break;
         }
-        let param: String = syntax[1..syntax.len()-1].to_string();
-        if parameter_number == 1 {
-            file_path = param;
-        } else if param.contains("=") {
-            // type override
-            let custom_type_parts: Vec<&str> = param.split("=").collect();
-            if custom_type_parts.len() != 2 {
-                panic!("Could not parse JSON Schema Invalid custom type: {}\n", param);
+        attributes.push(item_tokens[position..position + 2].to_vec());
+        position += 2;
+    }
+    let visibility_start: usize = position;
+    if matches!(item_tokens.get(position), Some(TokenTree::Ident(ident)) if ident.to_string() == "pub") {
+        position += 1;
+        if matches!(item_tokens.get(position), Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Parenthesis) {
+            position += 1;
+        }
+    }
+    let visibility: String = get_tokens_text(&item_tokens[visibility_start..position]);
+    let (item_kind, item_name): (String, String) = match (item_tokens.get(position), item_tokens.get(position + 1)) {
+        (Some(TokenTree::Ident(kind)), Some(TokenTree::Ident(name))) => (kind.to_string(), name.to_string()),
+        _                                                            => panic!("Could not parse JSON Schema attribute, it goes on a mod or a struct\n"),
+    };
+    match (item_kind.as_str(), &item_tokens[position + 2..]) {
+        ("mod", [TokenTree::Group(content)]) if content.delimiter() == Delimiter::Brace => {
+            let struct_text: String = match &pointer {
+                Some(pointer) => json_schema_file_pointer_impl(file_path.clone(), pointer, None, &options),
+                None          => json_schema_file_impl(file_path.clone(), &options),
+            };
+            let generated: TokenStream = TokenStream::from_str(&struct_text)
+                .unwrap_or_else(|err_msg| panic!("Could not parse error {} from JSON Schema in {}\n", err_msg, file_path));
+            let mut module_content: TokenStream = content.stream();
+            module_content.extend(generated);
+            let mut module_group: Group = Group::new(Delimiter::Brace, module_content);
+            module_group.set_span(content.span());
+            let mut rslt: TokenStream = TokenStream::from_str("#[allow(non_snake_case, non_camel_case_types)]").unwrap();
+            rslt.extend(item_tokens[..position + 2].iter().cloned());
+            rslt.extend(std::iter::once(TokenTree::Group(module_group)));
+            rslt
+        },
+        ("struct", [TokenTree::Punct(semicolon)]) if semicolon.as_char() == ';' => {
+            let pointer: String = pointer.unwrap_or_else(|| panic!("Could not parse JSON Schema attribute, a struct needs a pointer to its definition, e.g. pointer = \"/$defs/{}\"\n", item_name));
+            if options.module_name.is_none() {
+                options.visibility = Some(visibility);
+            }
+            for attribute in &attributes {
+                let attribute_text: String = get_tokens_text(attribute);
+                match attribute_text.strip_prefix("#[derive(").and_then(|derive_text| derive_text.strip_suffix(")]")) {
+                    Some(derive_text) => options.custom_derive_map.entry(item_name.clone()).or_default().extend(derive_text.split(',').map(|derive| derive.trim().to_string())),
+                    None              => options.custom_attribute_map.entry(item_name.clone()).or_default().push(attribute_text),
+                }
             }
-            custom_type_map.insert(custom_type_parts[0].to_string(), custom_type_parts[1].to_string());
-        } else if param.contains("->") {
-            // name override
-            let custom_name_parts: Vec<&str> = param.split("->").collect();
-            if custom_name_parts.len() != 2 {
-                panic!("Could not parse JSON Schema Invalid custom name: {}\n", param);
+            let struct_text: String = json_schema_file_pointer_impl(file_path.clone(), &pointer, Some(&item_name), &options);
+            let ts = TokenStream::from_str(&struct_text);
+            match ts {
+                Ok(rslt)      => rslt,
+                Err(err_msg)     => panic!("Could not parse error {} from JSON Schema in {}\n", err_msg, file_path)
             }
-            custom_name_map.insert(custom_name_parts[0].to_string(), custom_name_parts[1].to_string());
-        }    
-        parameter_number = parameter_number + 1;
+        },
+        ("struct", _) => panic!("Could not parse JSON Schema attribute, the fields of {} come from the schema, so it is declared as struct {};\n", item_name, item_name),
+        _             => panic!("Could not parse JSON Schema attribute, it goes on a mod or a struct\n"),
     }
-    let struct_text = json_schema_file_impl(file_path.clone(), &custom_name_map, &custom_type_map);
+}
+
+/// include JSON Type Definition (RFC 8927) from a file,
+/// supports the same custom names, types and collections as json_schema_file
+///
+/// jtd_file("<filename>", ...);
+///
+/// the root type is named after the file, e.g. "src/user.jtd.json" gives User, or can be renamed with "->name"
+///
+#[proc_macro]
+pub fn jtd_file(parameters: TokenStream) -> TokenStream {
+    let (file_path, _, options) = parse_file_parameters(parameters, None);
+    let struct_text = jtd_file_impl(file_path.clone(), &options);
     let ts  = TokenStream::from_str(&struct_text);
     match ts {
-        Ok(rslt)      => return rslt,
-        Err(err_msg)     => panic!("Could not parse error {} from JSON Schema in {}\n", err_msg, file_path)
+        Ok(rslt)      => rslt,
+        Err(err_msg)     => panic!("Could not parse error {} from JSON Type Definition in {}\n", err_msg, file_path)
     }
-}    
+}
+
+/// generate types for the components/schemas of an OpenAPI 3.0 or 3.1 document in a file, JSON or YAML,
+/// supports the same custom names, types and collections as json_schema_file
+///
+/// openapi_file("<filename>", ...);
+///
+/// "$operationBodies=true" also generates request and response body types per operation,
+/// e.g. ListPetsRequest and ListPetsResponse200
+///
+#[proc_macro]
+pub fn openapi_file(parameters: TokenStream) -> TokenStream {
+    let (file_path, _, options) = parse_file_parameters(parameters, None);
+    let struct_text = openapi_file_impl(file_path.clone(), &options);
+    let ts  = TokenStream::from_str(&struct_text);
+    match ts {
+        Ok(rslt)      => rslt,
+        Err(err_msg)     => panic!("Could not parse error {} from OpenAPI in {}\n", err_msg, file_path)
+    }
+}
+
+/// parse the file path and options given to json_schema_file, json_schema_dir, jtd_file, openapi_file or json_schema,
+/// each either a string, e.g. "amount=i64", or structured, e.g. types { amount: i64 },
+/// along with the value of the extra option the macro takes, e.g. the file pattern of json_schema_dir
+fn parse_file_parameters(parameters: TokenStream, extra_key: Option<&str>) -> (String, Option<String>, GeneratorOptions) {
+    let mut parameter_number: i32 = 1;
+    let mut file_path: String = "".to_string();
+    let mut extra_value: Option<String> = None;
+    let mut options: GeneratorOptions = GeneratorOptions::default();
+    for parameter in split_parameters(parameters.into_iter().collect()) {
+        if let [TokenTree::Literal(literal)] = parameter.as_slice() {
+            let param: String = get_string_literal(literal)
+                .unwrap_or_else(|| panic!("Could not parse JSON Schema Invalid option: {}\n", literal));
+            if parameter_number == 1 {
+                file_path = param;
+            } else if extra_key == Some("pattern") && parameter_number == 2 && !param.contains('=') && !param.contains("->") {
+                // file pattern for json_schema_dir
+                extra_value = Some(param);
+            } else {
+                parse_string_parameter(&param, &mut options);
+            }
+            parameter_number += 1;
+            continue;
+        }
+        let option_name: String = match parameter.first() {
+            Some(TokenTree::Ident(ident)) => ident.to_string(),
+            _                             => panic!("Could not parse JSON Schema Invalid option: {}\n", get_tokens_text(&parameter)),
+        };
+        match (option_name.as_str(), &parameter[1..]) {
+            ("path" | "file", [TokenTree::Punct(eq), value @ ..]) if eq.as_char() == '=' => file_path = get_option_value(value),
+            (_, [TokenTree::Punct(eq), value @ ..]) if extra_key == Some(option_name.as_str()) && eq.as_char() == '=' => extra_value = Some(get_option_value(value)),
+            (_, [TokenTree::Punct(eq), value @ ..]) if eq.as_char() == '=' => {
+                set_option(&get_option_key(&option_name), &get_option_value(value), &mut options);
+            },
+            ("derive", [TokenTree::Group(list)]) if list.delimiter() == Delimiter::Parenthesis => {
+                options.extra_derives.extend(split_parameters(list.stream().into_iter().collect()).iter().map(|derive| get_tokens_text(derive)));
+            },
+            ("roots", [TokenTree::Group(list)]) if list.delimiter() == Delimiter::Parenthesis => {
+                options.root_pointers.extend(get_option_list(&parameter[1..]));
+            },
+            ("attr", [TokenTree::Group(list)]) if list.delimiter() == Delimiter::Parenthesis => {
+                options.extra_attributes.extend(split_parameters(list.stream().into_iter().collect()).iter().map(|attribute| get_tokens_text(attribute)));
+            },
+            (_, [TokenTree::Group(block)]) if block.delimiter() == Delimiter::Brace => {
+                for (key, value) in parse_option_block(&option_name, block) {
+                    match option_name.as_str() {
+                        "rename"      => { options.custom_name_map.insert(key, get_option_value(&value)); },
+                        "types"       => { options.custom_type_map.insert(key, get_option_value(&value)); },
+                        "collections" => { options.custom_collection_map.insert(key, parse_collection(&get_option_value(&value), &[Collection::Vec, Collection::BTreeSet, Collection::HashSet, Collection::Array, Collection::NonEmpty])); },
+                        "type_derive" => options.custom_derive_map.entry(key).or_default().extend(get_option_list(&value)),
+                        "type_attr"   => options.custom_attribute_map.entry(key).or_default().extend(get_option_list(&value)),
+                        _             => panic!("Could not parse JSON Schema Unknown option: {}\n", option_name),
+                    }
+                }
+            },
+            _ => panic!("Could not parse JSON Schema Unknown option: {}\n", get_tokens_text(&parameter)),
+        }
+    }
+    (file_path, extra_value, options)
+}
+
+/// parse an option given as a string, e.g. "$uniqueItems=BTreeSet", "amount=i64" or "bank statement->Bank_Statement"
+fn parse_string_parameter(param: &str, options: &mut GeneratorOptions) {
+    if param.contains("$derive=") {
+        // extra derives, for every type or one type
+        let custom_derive_parts: Vec<&str> = param.splitn(2, "$derive=").collect();
+        let derives: Vec<String> = custom_derive_parts[1].split(',').map(|derive| derive.trim().to_string()).filter(|derive| !derive.is_empty()).collect();
+        match custom_derive_parts[0] {
+            ""        => options.extra_derives.extend(derives),
+            type_name => options.custom_derive_map.entry(type_name.to_string()).or_default().extend(derives),
+        }
+    } else if param.contains("$attr=") {
+        // extra attribute, for every type or one type
+        let custom_attribute_parts: Vec<&str> = param.splitn(2, "$attr=").collect();
+        let attribute: String = if custom_attribute_parts[1].trim().starts_with("#[") { custom_attribute_parts[1].trim().to_string() } else { format!("#[{}]", custom_attribute_parts[1].trim()) };
+        match custom_attribute_parts[0] {
+            ""        => options.extra_attributes.push(attribute),
+            type_name => options.custom_attribute_map.entry(type_name.to_string()).or_default().push(attribute),
+        }
+    } else if let Some((option_key, option_value)) = param.strip_prefix('$').and_then(|option_text| option_text.split_once('=')) {
+        set_option(option_key, option_value, options);
+    } else if param.starts_with('$') {
+        panic!("Could not parse JSON Schema Unknown option: {}\n", param);
+    } else if param.contains("[]=") {
+        // collection override
+        let custom_collection_parts: Vec<&str> = param.splitn(2, "[]=").collect();
+        let collection = parse_collection(custom_collection_parts[1], &[Collection::Vec, Collection::BTreeSet, Collection::HashSet, Collection::Array, Collection::NonEmpty]);
+        options.custom_collection_map.insert(custom_collection_parts[0].to_string(), collection);
+    } else if param.contains("->") {
+        // name override, before type overrides, as the names may hold an =
+        let custom_name_parts: Vec<&str> = param.split("->").collect();
+        if custom_name_parts.len() != 2 {
+            panic!("Could not parse JSON Schema Invalid custom name: {}\n", param);
+        }
+        options.custom_name_map.insert(custom_name_parts[0].to_string(), custom_name_parts[1].to_string());
+    } else if let Some((field_name, rust_type_name)) = param.split_once('=') {
+        // type override, where the type may hold an = of its own
+        options.custom_type_map.insert(field_name.to_string(), rust_type_name.to_string());
+    } else {
+        panic!("Could not parse JSON Schema Unknown option: {}\n", param);
+    }
+}
+
+/// set an option by its string name, e.g. "uniqueItems", as given by "$uniqueItems=..." or unique_items = ...
+fn set_option(option_key: &str, option_value: &str, options: &mut GeneratorOptions) {
+    let option_value: &str = option_value.trim();
+    match option_key {
+        "schema"          => match Draft::from_name(option_value) {
+            // draft override
+            Some(draft) => options.draft_override = Some(draft),
+            None        => panic!("Could not parse JSON Schema Unknown draft: {}\n", option_value)
+        },
+        "uniqueItems"     => options.unique_items = parse_collection(option_value, &[Collection::Vec, Collection::BTreeSet, Collection::HashSet]),
+        "fixedItems"      => options.fixed_items = parse_collection(option_value, &[Collection::Vec, Collection::Array]),
+        "minItems"        => options.min_items = parse_collection(option_value, &[Collection::Vec, Collection::NonEmpty]),
+        "format"          => match SourceFormat::from_name(option_value) {
+            // file format override
+            Some(source_format) => options.source_format = Some(source_format),
+            None                => panic!("Could not parse JSON Schema Unknown format: {}\n", option_value)
+        },
+        "operationBodies" => options.operation_bodies = parse_flag(option_key, option_value),
+        "jsonSchema"      => options.json_schema_fn = parse_flag(option_key, option_value),
+        "builders"        => options.builders = parse_flag(option_key, option_value),
+        "serde"           => options.serde_crate = Some(option_value.to_string()),
+        "serdeJson"       => options.serde_json_crate = Some(option_value.to_string()),
+        "regex"           => options.regex_crate = Some(option_value.to_string()),
+        "freeFormObject"  => options.free_form_object = Some(option_value.to_string()),
+        "module"          => options.module_name = Some(option_value.to_string()),
+        "roots"           => options.root_pointers.extend(option_value.split(',').map(|root_pointer| root_pointer.trim().to_string())),
+        "visibility"      => options.visibility = match option_value {
+            "private"              => Some("".to_string()),
+            "pub" | "pub(crate)"   => Some(option_value.to_string()),
+            _                      => panic!("Could not parse JSON Schema Invalid $visibility: {}\n", option_value)
+        },
+        _                 => panic!("Could not parse JSON Schema Unknown option: {}\n", option_key),
+    }
+}
+
+/// parse a true or false option
+fn parse_flag(option_key: &str, option_value: &str) -> bool {
+    match option_value {
+        "true"  => true,
+        "false" => false,
+        _       => panic!("Could not parse JSON Schema Invalid ${}: {}\n", option_key, option_value)
+    }
+}
+
+/// the string option name for a structured one, e.g. unique_items is "uniqueItems" and serde_json is "serdeJson"
+fn get_option_key(option_name: &str) -> String {
+    let mut option_key: String = "".to_string();
+    for (position, word) in option_name.split('_').enumerate() {
+        if word.is_empty() {
+            panic!("Could not parse JSON Schema Unknown option: {}\n", option_name);
+        }
+        option_key += &if position == 0 { word.to_string() } else { word[0..1].to_uppercase() + &word[1..] };
+    }
+    option_key
+}
+
+/// the entries of an option block, e.g. rename { "bank statement" => Bank_Statement } or types { amount: i64 },
+/// each key being a name or a string
+fn parse_option_block(option_name: &str, block: &Group) -> Vec<(String, Vec<TokenTree>)> {
+    split_parameters(block.stream().into_iter().collect()).into_iter().map(|entry| {
+        let key: String = match entry.first() {
+            Some(TokenTree::Ident(ident))     => ident.to_string(),
+            Some(TokenTree::Literal(literal)) => get_string_literal(literal)
+                .unwrap_or_else(|| panic!("Could not parse JSON Schema Invalid {} entry: {}\n", option_name, get_tokens_text(&entry))),
+            _                                 => panic!("Could not parse JSON Schema Invalid {} entry: {}\n", option_name, get_tokens_text(&entry)),
+        };
+        let value: &[TokenTree] = match &entry[1..] {
+            [TokenTree::Punct(arrow_1), TokenTree::Punct(arrow_2), value @ ..] if arrow_1.as_char() == '=' && arrow_2.as_char() == '>' => value,
+            [TokenTree::Punct(colon), value @ ..] if colon.as_char() == ':'                                                          => value,
+            _ => panic!("Could not parse JSON Schema Invalid {} entry: {}\n", option_name, get_tokens_text(&entry)),
+        };
+        if value.is_empty() {
+            panic!("Could not parse JSON Schema Invalid {} entry: {}\n", option_name, get_tokens_text(&entry));
+        }
+        (key, value.to_vec())
+    }).collect()
+}
+
+/// the value of an option, a string or else the Rust tokens given, e.g. HashMap<String, i64>
+fn get_option_value(value: &[TokenTree]) -> String {
+    match value {
+        [TokenTree::Literal(literal)] => get_string_literal(literal).unwrap_or_else(|| literal.to_string()),
+        _                             => get_tokens_text(value),
+    }
+}
+
+/// the values of an option that takes a list, e.g. (Debug, PartialEq), or a single value
+fn get_option_list(value: &[TokenTree]) -> Vec<String> {
+    match value {
+        [TokenTree::Group(list)] if list.delimiter() == Delimiter::Parenthesis => {
+            split_parameters(list.stream().into_iter().collect()).iter().map(|item| get_option_value(item)).collect()
+        },
+        _ => vec![get_option_value(value)],
+    }
+}
+
+/// split tokens at the commas between parameters, leaving those within groups and generic arguments
+fn split_parameters(tokens: Vec<TokenTree>) -> Vec<Vec<TokenTree>> {
+    let mut parameters: Vec<Vec<TokenTree>> = Vec::new();
+    let mut parameter: Vec<TokenTree> = Vec::new();
+    let mut angle_depth: i32 = 0;
+    for token in tokens {
+        if let TokenTree::Punct(punct) = &token {
+            match punct.as_char() {
+                ',' if angle_depth == 0 => {
+                    parameters.push(std::mem::take(&mut parameter));
+                    continue;
+                },
+                '<' => angle_depth += 1,
+                // not the > of a => or ->
+                '>' if !matches!(parameter.last(), Some(TokenTree::Punct(previous)) if previous.spacing() == Spacing::Joint && (previous.as_char() == '=' || previous.as_char() == '-')) => angle_depth -= 1,
+                _   => {},
+            }
+        }
+        parameter.push(token);
+    }
+    if !parameter.is_empty() {
+        parameters.push(parameter);
+    }
+    parameters
+}
+
+/// the text of a string literal, plain or raw, or None for another literal
+fn get_string_literal(literal: &Literal) -> Option<String> {
+    let syntax: String = literal.to_string();
+    if let Some(raw_text) = syntax.strip_prefix('r') {
+        let hashes: usize = raw_text.len() - raw_text.trim_start_matches('#').len();
+        return raw_text.get(hashes + 1..raw_text.len() - hashes - 1).map(|text| text.to_string());
+    }
+    if syntax.len() < 2 || !syntax.starts_with('"') || !syntax.ends_with('"') {
+        return None;
+    }
+    Some(unescape_string(&syntax[1..syntax.len()-1]))
+}
+
+/// the value of the body of a plain string literal, with its escapes, e.g. \n or \u{e9}, replaced
+fn unescape_string(body: &str) -> String {
+    let mut text: String = String::with_capacity(body.len());
+    let mut chars = body.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n')  => text.push('\n'),
+            Some('r')  => text.push('\r'),
+            Some('t')  => text.push('\t'),
+            Some('0')  => text.push('\0'),
+            Some('\\') => text.push('\\'),
+            Some('\'') => text.push('\''),
+            Some('"')  => text.push('"'),
+            Some('x')  => {
+                let hex: String = chars.by_ref().take(2).collect();
+                match u8::from_str_radix(&hex, 16) {
+                    Ok(code) if hex.len() == 2 && code < 0x80 => text.push(code as char),
+                    _ => panic!("Could not parse JSON Schema Invalid escape \\x{} in \"{}\"\n", hex, body),
+                }
+            },
+            Some('u')  => {
+                let mut hex: String = "".to_string();
+                if chars.next() == Some('{') {
+                    for hex_char in chars.by_ref() {
+                        if hex_char == '}' {
+                            break;
+                        }
+                        hex.push(hex_char);
+                    }
+                }
+                match u32::from_str_radix(&hex.replace('_', ""), 16).ok().and_then(char::from_u32) {
+                    Some(code) => text.push(code),
+                    None       => panic!("Could not parse JSON Schema Invalid escape \\u{{{}}} in \"{}\"\n", hex, body),
+                }
+            },
+            Some('\n') => {
+                // line continuation: the newline and the whitespace that follows are left out
+                while chars.peek().is_some_and(|next| next.is_whitespace()) {
+                    chars.next();
+                }
+            },
+            other      => panic!("Could not parse JSON Schema Unsupported escape \\{} in \"{}\"\n", other.map_or("".to_string(), |c| c.to_string()), body),
+        }
+    }
+    text
+}
+
+/// Rust tokens as compact text, e.g. HashMap<String, i64> rather than HashMap < String, i64 >
+fn get_tokens_text(tokens: &[TokenTree]) -> String {
+    let mut text: String = "".to_string();
+    let mut after_word: bool = false;
+    for token in tokens {
+        match token {
+            TokenTree::Ident(_) | TokenTree::Literal(_) => {
+                if after_word {
+                    text.push(' ');
+                }
+                text += &token.to_string();
+                after_word = true;
+            },
+            TokenTree::Punct(punct) => {
+                text.push(punct.as_char());
+                if punct.as_char() == ',' {
+                    text.push(' ');
+                }
+                after_word = false;
+            },
+            TokenTree::Group(group) => {
+                let inner_text: String = get_tokens_text(&group.stream().into_iter().collect::<Vec<TokenTree>>());
+                text += &match group.delimiter() {
+                    Delimiter::Parenthesis => format!("({})", inner_text),
+                    Delimiter::Bracket     => format!("[{}]", inner_text),
+                    Delimiter::Brace       => format!("{{{}}}", inner_text),
+                    Delimiter::None        => inner_text,
+                };
+                after_word = false;
+            },
+        }
+    }
+    text
+}
+
+/// parse a collection option, which must be one of the allowed collections
+fn parse_collection(collection_name: &str, allowed: &[Collection]) -> Collection {
+    match Collection::from_name(collection_name) {
+        Some(collection) if allowed.contains(&collection) => collection,
+        _                                                  => panic!("Could not parse JSON Schema Unknown collection: {}\n", collection_name)
+    }
+}
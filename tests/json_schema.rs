@@ -0,0 +1,227 @@
+//! Copyright (c) 2023  Ward van der Veer
+
+#![allow(non_snake_case, non_camel_case_types)]
+extern crate json_schema_parser;
+extern crate regex;
+extern crate serde;
+#[macro_use]
+extern crate serde_json;
+use std::convert::TryFrom;
+use std::fs;
+use serde_json::Value;
+
+mod as_written {
+    json_schema_parser::json_schema_file!("examples/example2.json", "$jsonSchema=true");
+}
+
+mod renamed {
+    json_schema_parser::json_schema_file!("examples/example2.json", "$jsonSchema=true", "veggie->Vegetable", "veggieName->name", "veggieLike=Option<i64>");
+}
+
+#[test]
+fn json_schema_round_trip() {
+    // $defs are named after the Rust types, and the $id is left out as it would move what the $refs resolve against
+    let source_text: String = fs::read_to_string("examples/example2.json").unwrap().replace("veggie\"", "Veggie\"");
+    let mut source: Value = serde_json::from_str(&source_text).unwrap();
+    source.as_object_mut().unwrap().remove("$id");
+    assert_eq!(as_written::Diet::json_schema(), source);
+}
+
+#[test]
+fn json_schema_renamed_round_trip() {
+    let schema: Value = renamed::Diet::json_schema();
+    assert_eq!(schema["properties"]["vegetables"]["items"], json!({ "$ref": "#/$defs/Vegetable" }));
+    assert_eq!(schema["$defs"]["Vegetable"]["required"], json!(["name", "veggieLike"]));
+    assert_eq!(schema["$defs"]["Vegetable"]["properties"]["name"], json!({ "type": "string", "description": "The name of the vegetable." }));
+    assert_eq!(schema["$defs"]["Vegetable"]["properties"]["veggieLike"], json!({
+        "anyOf": [{ "type": "integer", "format": "int64" }, { "type": "null" }],
+        "description": "Do I like this vegetable?"
+    }));
+    let vegetable: Value = renamed::Vegetable::json_schema();
+    assert_eq!(vegetable["$schema"], "https://json-schema.org/draft/2020-12/schema");
+    assert!(vegetable.get("$defs").is_none());
+    let diet = renamed::Diet { fruits: vec![], vegetables: vec![renamed::Vegetable { name: "leek".to_string(), veggieLike: Some(1) }] };
+    assert_eq!(serde_json::to_value(&diet).unwrap()["vegetables"][0], json!({ "name": "leek", "veggieLike": 1 }));
+}
+
+mod escaped {
+    json_schema_parser::json_schema_file!("examples/example2.json", "\u{76}eggie->Vege\x74able", "veggieName->\
+                                                                      name", "veggieLike=Option<\u{69}64>");
+}
+
+#[test]
+fn json_schema_escaped_parameters() {
+    let vegetable = escaped::Vegetable { name: "leek".to_string(), veggieLike: Some(1i64) };
+    assert_eq!(serde_json::to_value(&vegetable).unwrap(), json!({ "name": "leek", "veggieLike": 1 }));
+}
+
+mod equals_renamed {
+    json_schema_parser::json_schema_file!("examples/query.json", "page=size->page_size", "sort=Option<String>");
+}
+
+#[test]
+fn json_schema_rename_with_equals() {
+    let query = equals_renamed::Query { page_size: 20, sort: None };
+    assert_eq!(query.page_size, 20);
+}
+
+mod structured {
+    use std::collections::HashMap;
+    json_schema_parser::json_schema_file!(
+        path = "examples/example2.json",
+        json_schema = true,
+        unique_items = BTreeSet,
+        rename { veggie => Vegetable, "/$defs/veggie/properties/veggieName" => name },
+        types { veggieLike: HashMap<String, i64>, "$defs.veggie.veggieName": Box<str> },
+        collections { fruits: BTreeSet },
+        derive(Debug, PartialEq),
+        type_derive { Vegetable: (Eq) },
+        attr(#[serde(deny_unknown_fields)]),
+    );
+}
+
+#[test]
+fn json_schema_structured_parameters() {
+    let diet: structured::Diet = serde_json::from_value(json!({
+        "fruits": ["pear", "apple", "pear"],
+        "vegetables": [{ "name": "leek", "veggieLike": { "soup": 2 } }]
+    })).unwrap();
+    assert_eq!(diet.fruits.into_iter().collect::<Vec<String>>(), vec!["apple".to_string(), "pear".to_string()]);
+    assert_eq!(&*diet.vegetables[0].name, "leek");
+    assert_eq!(diet.vegetables[0].veggieLike["soup"], 2);
+    assert!(serde_json::from_value::<structured::Diet>(json!({ "fruits": [], "other": 1 })).is_err());
+    assert_eq!(structured::Diet::json_schema()["$defs"]["Vegetable"]["required"], json!(["name", "veggieLike"]));
+}
+
+#[json_schema_parser::json_schema(file = "examples/example2.json", derive(Debug))]
+mod model {
+    use std::fmt;
+
+    impl fmt::Display for Diet {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{} fruits, {} vegetables", self.fruits.len(), self.vegetables.len())
+        }
+    }
+}
+
+mod veggie_only {
+    /// a vegetable on its own
+    #[json_schema_parser::json_schema(file = "examples/example2.json", pointer = "/$defs/veggie", json_schema = true)]
+    #[derive(Debug, PartialEq)]
+    pub struct Vegetable;
+}
+
+#[test]
+fn json_schema_attribute() {
+    let diet: model::Diet = serde_json::from_value(json!({ "fruits": ["pear"], "vegetables": [{ "veggieName": "leek", "veggieLike": true }] })).unwrap();
+    assert_eq!(diet.to_string(), "1 fruits, 1 vegetables");
+    assert!(diet.vegetables[0].veggieLike);
+    let vegetable: veggie_only::Vegetable = serde_json::from_value(json!({ "veggieName": "leek", "veggieLike": true })).unwrap();
+    assert_eq!(vegetable, veggie_only::Vegetable { veggieName: "leek".to_string(), veggieLike: true });
+    assert_eq!(veggie_only::Vegetable::json_schema()["required"], json!(["veggieName", "veggieLike"]));
+}
+
+mod built {
+    json_schema_parser::json_schema_file!("examples/user.json", "$builders=true");
+}
+
+#[test]
+fn json_schema_builders() {
+    let login: built::User_login = built::User_login::builder().username("ada").build().unwrap();
+    assert_eq!(login.username, "ada");
+    assert_eq!(built::User_login::builder().build().err().unwrap(), "username is required");
+    assert_eq!(built::User::builder().login(login.clone()).build().err().unwrap(), "email is required");
+    let user: built::User = built::User::builder().email("ada@example.com").login(login).build().unwrap();
+    assert_eq!(user.nat, "NL");
+    assert_eq!(user.age, 30);
+    assert_eq!(user.tags, vec!["new".to_string()]);
+    assert_eq!(built::User::builder().email("a@b.c").login(user.login.clone()).age(41).build().unwrap().age, 41);
+}
+
+mod free_form {
+    json_schema_parser::json_schema_file!("examples/user.json", "$freeFormObject=BTreeMap");
+}
+
+#[test]
+fn json_schema_free_form_objects() {
+    let user: built::User = serde_json::from_value(json!({ "email": "a@b.c", "login": { "username": "ada" }, "settings": { "theme": "dark" } })).unwrap();
+    assert_eq!(user.settings["theme"], "dark");
+    let user: free_form::User = serde_json::from_value(json!({ "email": "a@b.c", "login": { "username": "ada" }, "settings": { "b": 1, "a": [] } })).unwrap();
+    assert_eq!(user.settings.keys().collect::<Vec<&String>>(), vec!["a", "b"]);
+    // typed maps follow $freeFormObject as well
+    let user: free_form::User = serde_json::from_value(json!({ "email": "a@b.c", "login": { "username": "ada" }, "scores": { "y": 2, "x": 1 } })).unwrap();
+    let scores: ::std::collections::BTreeMap<String, i32> = user.scores;
+    assert_eq!(scores.keys().collect::<Vec<&String>>(), vec!["x", "y"]);
+}
+
+mod paid {
+    json_schema_parser::json_schema_file!("examples/payment.json", "$derive=Debug, PartialEq");
+}
+
+#[test]
+fn json_schema_conditional_schemas() {
+    let cash: Value = json!({ "amount": 5, "method": { "kind": "cash" } });
+    let payment: paid::Payment = serde_json::from_value(cash.clone()).unwrap();
+    assert_eq!(payment.name, None);
+    assert_eq!(payment.method, paid::Method::Cash(paid::Method_Cash {}));
+    // the absent properties stay absent, as credit_card would need a name, and a name a billing_address
+    assert_eq!(serde_json::to_value(&payment).unwrap(), cash);
+    let card: Value = json!({
+        "amount": 5, "method": { "kind": "card", "last_digits": "4242" },
+        "name": "Ada", "credit_card": "4242424242424242", "billing_address": "Dam 1"
+    });
+    let payment: paid::Payment = serde_json::from_value(card.clone()).unwrap();
+    assert_eq!(payment.method, paid::Method::Card(paid::Method_Card { last_digits: "4242".to_string() }));
+    assert_eq!(payment.billing_address.as_deref(), Some("Dam 1"));
+    assert_eq!(serde_json::to_value(&payment).unwrap(), card);
+}
+
+mod priced {
+    json_schema_parser::json_schema_file!("examples/price.json", "$regex=::regex", "$derive=Debug");
+}
+
+#[test]
+fn json_schema_constrained_scalars() {
+    let currency: priced::Currency = priced::Currency::try_from("EUR".to_string()).unwrap();
+    assert_eq!(currency.len(), 3);
+    assert_eq!(currency.to_string(), "EUR");
+    assert_eq!(priced::Currency::try_from("euro".to_string()).err().unwrap(), "euro must match ^[A-Z]{3}$");
+    assert_eq!(priced::Amount::try_from(0.0).err().unwrap(), "0 must be greater than 0");
+    assert!(priced::Amount::try_from(19.99).is_ok());
+    assert!(priced::Amount::try_from(19.995).is_err());
+    assert_eq!(*priced::Quantity::default(), 1);
+    let price: priced::Price = serde_json::from_value(json!({ "currency": "EUR", "amount": 19.99 })).unwrap();
+    assert_eq!(*price.amount, 19.99);
+    assert_eq!(serde_json::to_value(&price).unwrap(), json!({ "currency": "EUR", "amount": 19.99, "quantity": 1 }));
+    let error: String = serde_json::from_value::<priced::Price>(json!({ "currency": "EUR", "amount": 1, "quantity": 100 })).unwrap_err().to_string();
+    assert!(error.contains("100 must be at most 99"));
+}
+
+mod priced_built {
+    json_schema_parser::json_schema_file!("examples/price.json", "$regex=::regex", "$builders=true");
+}
+
+#[test]
+fn json_schema_constrained_builders() {
+    let price: priced_built::Price = priced_built::Price::builder().currency("EUR").amount(19.99).quantity(3).build().unwrap();
+    assert_eq!(*price.quantity, 3);
+    assert_eq!(*priced_built::Price::builder().currency("EUR").amount(1.0).build().unwrap().quantity, 1);
+    assert_eq!(priced_built::Price::builder().currency("EUR").amount(1.0).quantity(100).build().err().unwrap(), "100 must be at most 99");
+    assert_eq!(priced_built::Price::builder().currency("euro").amount(1.0).build().err().unwrap(), "euro must match ^[A-Z]{3}$");
+    assert_eq!(priced_built::Price::builder().amount(1.0).build().err().unwrap(), "currency is required");
+}
+
+mod schemas {
+    json_schema_parser::json_schema_dir!("examples/schemas", "*.json", "$derive=Debug, PartialEq");
+}
+
+#[test]
+fn json_schema_dir_refs() {
+    let line: schemas::order_line::Order_line = serde_json::from_value(json!({
+        "customer": { "name": "Ada", "billing": { "street": "Dam 1", "country": "NL" }, "shipping": [], "country": "BE" },
+        "quantity": 2
+    })).unwrap();
+    assert_eq!(line.customer.billing.country, schemas::address::Country::Nl);
+    assert_eq!(line.customer.country, schemas::address::Country::Be);
+    assert_eq!(line.quantity, 2);
+}